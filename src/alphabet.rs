@@ -0,0 +1,79 @@
+use super::error::SudokuError;
+
+const DIGITS: &str = "123456789";
+const HEX: &str = "0123456789ABCDEF";
+const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const ALNUM: &str = "123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Symbols used to read and print values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Alphabet {
+  /// Decimal numbers, a run of digits is one value and `0` is ignored.
+  Decimal,
+  /// One character per value, `symbols[value]` is the symbol of `value`.
+  Symbols(Vec<char>),
+}
+
+impl Alphabet {
+  /// Alphabet of the given characters, one per value.
+  pub fn symbols(symbols: &str) -> Result<Alphabet, SudokuError> {
+    let list: Vec<char> = symbols.chars().collect();
+    for (i, &ch) in list.iter().enumerate() {
+      if ch == '.' || ch.is_whitespace() || list[.. i].contains(&ch) {
+        return Err(SudokuError::InvalidAlphabet(format!("invalid or repeated symbol '{}'", ch)));
+      }
+    }
+    Ok(Alphabet::Symbols(list))
+  }
+
+  /// Alphabet for `count` values by name: `decimal`, `digits` (1-9), `hex`
+  /// (0-F), `letters` (A-Z) or `alnum` (1-9 then A-Z). Any other name is
+  /// taken as the list of symbols itself.
+  pub fn named(name: &str, count: usize) -> Result<Alphabet, SudokuError> {
+    let symbols = match name {
+      "decimal" => return Ok(Alphabet::Decimal),
+      "digits" => DIGITS,
+      "hex" => HEX,
+      "letters" => LETTERS,
+      "alnum" => ALNUM,
+      custom => custom,
+    };
+    if symbols.chars().count() < count {
+      return Err(SudokuError::InvalidAlphabet(format!("\"{}\" has less than {} symbols", name, count)));
+    }
+    Alphabet::symbols(&symbols.chars().take(count).collect::<String>())
+  }
+
+  /// Number of values the alphabet can represent, None if unbounded.
+  pub fn count(&self) -> Option<usize> {
+    match self {
+      Alphabet::Decimal => None,
+      Alphabet::Symbols(list) => Some(list.len()),
+    }
+  }
+
+  /// Value of a symbol, accepting lower case letters for upper case symbols.
+  pub fn value(&self, ch: char) -> Option<usize> {
+    match self {
+      Alphabet::Decimal => ch.to_digit(10).and_then(|d| (d as usize).checked_sub(1)),
+      Alphabet::Symbols(list) => list.iter().position(|&s| s == ch)
+        .or_else(|| list.iter().position(|&s| s == ch.to_ascii_uppercase())),
+    }
+  }
+
+  /// Symbol of `value`.
+  pub fn symbol(&self, value: usize) -> String {
+    match self {
+      Alphabet::Decimal => (value + 1).to_string(),
+      Alphabet::Symbols(list) => list[value].to_string(),
+    }
+  }
+
+  /// Width of the widest symbol for `count` values.
+  pub fn width(&self, count: usize) -> usize {
+    match self {
+      Alphabet::Decimal => (count.ilog10() + 1) as usize,
+      Alphabet::Symbols(_) => 1,
+    }
+  }
+}
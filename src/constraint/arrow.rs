@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use super::super::{
+  error::SudokuError,
+  sudoku::{
+    FLAG_OK,
+    Collapsed,
+    Constraint,
+    Note,
+    Rule,
+    Sudoku,
+  },
+};
+
+/// The value of the circle is the sum of the values along the arrow. Values
+/// may repeat on the arrow.
+#[derive(Clone)]
+pub struct Arrow {
+  circle: usize,
+  cells: Vec<usize>,
+  // The circle followed by the arrow.
+  all: Vec<usize>,
+}
+
+// Smallest and largest value (1-based) of `note`.
+fn bounds<N: Note>(rule: &Rule<N>, note: N) -> Option<(usize, usize)> {
+  let mut values = rule.note.iter().enumerate().filter(|&(_, &bit)| note & bit != rule.zero);
+  let (low, _) = values.next()?;
+  let high = values.last().map_or(low, |(v, _)| v);
+  Some((low + 1, high + 1))
+}
+
+// Every value (1-based) from `low` to `high`.
+fn range<N: Note>(rule: &Rule<N>, low: usize, high: usize) -> N {
+  let mut result = rule.zero;
+  for (v, &bit) in rule.note.iter().enumerate() {
+    if low <= v + 1 && v < high {
+      result = result | bit;
+    }
+  }
+  result
+}
+
+impl Arrow {
+  pub fn new(circle: usize, cells: Vec<usize>) -> Arrow {
+    let mut all = vec![circle];
+    all.extend(cells.iter().copied());
+    Arrow {
+      circle,
+      cells,
+      all,
+    }
+  }
+
+  pub fn circle(&self) -> usize {
+    self.circle
+  }
+
+  pub fn cells(&self) -> &[usize] {
+    &self.cells
+  }
+
+  /// Attach the arrow to the circle and each cell of the arrow.
+  pub fn apply<N: Note>(self, dst: &mut Rule<N>) -> Result<(), SudokuError> {
+    let cells = self.all.clone();
+    dst.add_shared_constraint(&cells, Arc::new(self))
+  }
+
+  // The circle lies between the smallest and largest possible sums of the
+  // arrow, and each cell of the arrow has to leave room for the others.
+  fn prune<N: Note>(&self, sudoku: &mut Sudoku<N>) -> Collapsed {
+    let rule = sudoku.rule;
+    let mut result = Collapsed::Unchanged;
+
+    let mut arrow = vec![];
+    for &i in self.cells.iter() {
+      match bounds(rule, sudoku.board[i]) {
+        Some(b) => arrow.push(b),
+        None => return Collapsed::Error,
+      }
+    }
+    let min_sum: usize = arrow.iter().map(|&(low, _)| low).sum();
+    let max_sum: usize = arrow.iter().map(|&(_, high)| high).sum();
+    if !sudoku.restrict(self.circle, range(rule, min_sum, max_sum), &mut result) {
+      return Collapsed::Error;
+    }
+
+    let (circle_low, circle_high) = match bounds(rule, sudoku.board[self.circle]) {
+      Some(b) => b,
+      None => return Collapsed::Error,
+    };
+    for (&i, &(low, high)) in self.cells.iter().zip(arrow.iter()) {
+      let others_max = max_sum - high;
+      let others_min = min_sum - low;
+      if circle_high < others_min {
+        return Collapsed::Error;
+      }
+      let allowed = range(rule, circle_low.saturating_sub(others_max), circle_high - others_min);
+      if !sudoku.restrict(i, allowed, &mut result) {
+        return Collapsed::Error;
+      }
+    }
+    result
+  }
+}
+
+impl<N: Note> Constraint<N> for Arrow {
+  fn added(&self, sudoku: &mut Sudoku<N>, _index: usize) {
+    self.prune(sudoku);
+  }
+
+  fn collapsed(&self, sudoku: &mut Sudoku<N>, _index: usize, _value: usize, _note: N) -> Collapsed {
+    self.prune(sudoku)
+  }
+
+  fn watch_changes(&self) -> bool {
+    true
+  }
+
+  fn changed(&self, sudoku: &mut Sudoku<N>, _index: usize) -> Collapsed {
+    self.prune(sudoku)
+  }
+
+  fn neighbours(&self) -> &[usize] {
+    &self.all
+  }
+
+  fn name(&self) -> &str {
+    "arrow"
+  }
+
+  // A given circle smaller than the givens of its arrow, or a fully given
+  // arrow not adding up.
+  fn conflict(&self, sudoku: &Sudoku<N>, index: usize, value: usize) -> Option<usize> {
+    let rule = sudoku.rule;
+    if !self.all.contains(&index) {
+      return None;
+    }
+    let given = |i: usize| {
+      if i == index {
+        Some(value + 1)
+      } else if sudoku.flags[i] & FLAG_OK == FLAG_OK {
+        rule.value(sudoku.board[i]).map(|v| v + 1)
+      } else {
+        None
+      }
+    };
+    let circle = given(self.circle)?;
+    let mut sum = 0;
+    let mut complete = true;
+    for &i in self.cells.iter() {
+      match given(i) {
+        Some(v) => sum += v,
+        None => {
+          sum += 1;
+          complete = false;
+        },
+      }
+    }
+    if sum > circle || (complete && sum != circle) {
+      self.all.iter().copied().find(|&i| i != index && given(i).is_some())
+    } else {
+      None
+    }
+  }
+}
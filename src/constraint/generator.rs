@@ -3,9 +3,11 @@ use super::{
   ConstraintList,
   ConstraintListGenerator,
   GroupGenerator,
+  super::error::SudokuError,
   super::sudoku::Note,
 };
 
+/// Columns of a `width` x `height` area at (`dx`, `dy`).
 pub struct GGVertical {
   board_width: usize,
   board_height: usize,
@@ -45,6 +47,7 @@ impl GroupGenerator for GGVertical {
 
 }
 
+/// Rows of a `width` x `height` area at (`dx`, `dy`).
 pub struct GGHorizontal {
   board_width: usize,
   board_height: usize,
@@ -84,6 +87,8 @@ impl GroupGenerator for GGHorizontal {
   }
 }
 
+/// `width` x `height` boxes of `block_width` x `block_height` cells at
+/// (`dx`, `dy`).
 pub struct GGBlock {
   board_width: usize,
   board_height: usize,
@@ -127,3 +132,239 @@ impl GroupGenerator for GGBlock {
   }
 
 }
+
+/// Extra windows of hyper sudoku (windoku): one box of `block_width` x
+/// `block_height` cells between every 2 x 2 boxes of a `width` x `height`
+/// grid of boxes at (`dx`, `dy`), each window one cell away from the previous
+/// one. Windows only fit when `width <= block_width + 1` and
+/// `height <= block_height + 1`.
+pub struct GGWindow {
+  board_width: usize,
+  board_height: usize,
+  width: usize,
+  height: usize,
+  block_width: usize,
+  block_height: usize,
+  dx: usize,
+  dy: usize,
+}
+
+impl GGWindow {
+  pub fn new<T, N>(nd: &ConstraintListGenerator<T, N>, width: usize, height: usize, block_width: usize, block_height: usize, dx: usize, dy: usize) -> GGWindow
+  where T: ConstraintList<N>, N: Note
+  {
+    GGWindow {
+      board_width: nd.width,
+      board_height: nd.height,
+      width,
+      height,
+      block_width,
+      block_height,
+      dx,
+      dy,
+    }
+  }
+
+  /// Whether every window lies inside its grid of boxes.
+  pub fn fits(&self) -> bool {
+    self.width <= self.block_width + 1 && self.height <= self.block_height + 1
+  }
+}
+
+impl GroupGenerator for GGWindow {
+  fn get_board(&self) -> (usize, usize) {
+    (self.board_width, self.board_height)
+  }
+
+  fn get_size(&self) -> (usize, usize) {
+    (self.width.saturating_sub(1) * self.height.saturating_sub(1), self.block_width * self.block_height)
+  }
+
+  fn get_member(&self, group: usize, member: usize) -> usize {
+    let columns = self.width - 1;
+    group % columns * (self.block_width + 1) + 1 + member % self.block_width + self.dx
+    +(group / columns * (self.block_height + 1) + 1 + member / self.block_width + self.dy) * self.board_width
+  }
+}
+
+// Check that a `size` x `size` area at (`dx`, `dy`) fits on the board,
+// reporting its far corner otherwise.
+fn check_square(board_width: usize, board_height: usize, size: usize, dx: usize, dy: usize) -> Result<(), SudokuError> {
+  if dx + size <= board_width && dy + size <= board_height {
+    return Ok(());
+  }
+  let index = (dx + size).saturating_sub(1) + (dy + size).saturating_sub(1) * board_width;
+  Err(SudokuError::InvalidIndex { index, size: board_width * board_height })
+}
+
+/// Main diagonal, from top left to bottom right, of a `size` x `size` area at
+/// (`dx`, `dy`).
+pub struct GGDiagonal {
+  board_width: usize,
+  board_height: usize,
+  size: usize,
+  dx: usize,
+  dy: usize,
+}
+
+impl GGDiagonal {
+  /// Fails with [`SudokuError::InvalidIndex`] if the area doesn't fit on the
+  /// board.
+  pub fn new<T, N>(nd: &ConstraintListGenerator<T, N>, size: usize, dx: usize, dy: usize) -> Result<GGDiagonal, SudokuError>
+  where T: ConstraintList<N>, N: Note
+  {
+    check_square(nd.width, nd.height, size, dx, dy)?;
+    Ok(GGDiagonal {
+      board_width: nd.width,
+      board_height: nd.height,
+      size,
+      dx,
+      dy,
+    })
+  }
+}
+
+impl GroupGenerator for GGDiagonal {
+  fn get_board(&self) -> (usize, usize) {
+    (self.board_width, self.board_height)
+  }
+
+  fn get_size(&self) -> (usize, usize) {
+    (1, self.size)
+  }
+
+  fn get_member(&self, _group: usize, member: usize) -> usize {
+    member + self.dx + (member + self.dy) * self.board_width
+  }
+}
+
+/// Anti diagonal, from top right to bottom left, of a `size` x `size` area
+/// at (`dx`, `dy`).
+pub struct GGAntiDiagonal {
+  board_width: usize,
+  board_height: usize,
+  size: usize,
+  dx: usize,
+  dy: usize,
+}
+
+impl GGAntiDiagonal {
+  /// Fails with [`SudokuError::InvalidIndex`] if the area doesn't fit on the
+  /// board.
+  pub fn new<T, N>(nd: &ConstraintListGenerator<T, N>, size: usize, dx: usize, dy: usize) -> Result<GGAntiDiagonal, SudokuError>
+  where T: ConstraintList<N>, N: Note
+  {
+    check_square(nd.width, nd.height, size, dx, dy)?;
+    Ok(GGAntiDiagonal {
+      board_width: nd.width,
+      board_height: nd.height,
+      size,
+      dx,
+      dy,
+    })
+  }
+}
+
+impl GroupGenerator for GGAntiDiagonal {
+  fn get_board(&self) -> (usize, usize) {
+    (self.board_width, self.board_height)
+  }
+
+  fn get_size(&self) -> (usize, usize) {
+    (1, self.size)
+  }
+
+  fn get_member(&self, _group: usize, member: usize) -> usize {
+    self.size - 1 - member + self.dx + (member + self.dy) * self.board_width
+  }
+}
+
+/// Irregular regions of a jigsaw sudoku, from a map holding the region id of
+/// every cell. Every region must have the same number of cells.
+pub struct GGRegion {
+  board_width: usize,
+  board_height: usize,
+  map: Vec<usize>,
+  members: Vec<Vec<usize>>,
+}
+
+impl GGRegion {
+  /// Regions of `map`, with one id per cell of the board. Ids are renumbered
+  /// from 0 in order of first appearance.
+  pub fn new<T, N>(nd: &ConstraintListGenerator<T, N>, map: &[usize]) -> Result<GGRegion, SudokuError>
+  where T: ConstraintList<N>, N: Note
+  {
+    let size = nd.width * nd.height;
+    if map.len() != size {
+      return Err(SudokuError::CellCount { expected: size, found: map.len() });
+    }
+    let mut ids = vec![];
+    let mut members: Vec<Vec<usize>> = vec![];
+    let mut renumbered = vec![];
+    for (i, &id) in map.iter().enumerate() {
+      let region = match ids.iter().position(|&other| other == id) {
+        Some(region) => region,
+        None => {
+          ids.push(id);
+          members.push(vec![]);
+          ids.len() - 1
+        },
+      };
+      members[region].push(i);
+      renumbered.push(region);
+    }
+    if let Some(region) = members.iter().find(|m| m.len() != members[0].len()) {
+      let (row, column) = (region[0] / nd.width + 1, region[0] % nd.width + 1);
+      return Err(SudokuError::InvalidRegions(format!(
+        "region of r{}c{} has {} cells instead of {}", row, column, region.len(), members[0].len()
+      )));
+    }
+    Ok(GGRegion {
+      board_width: nd.width,
+      board_height: nd.height,
+      map: renumbered,
+      members,
+    })
+  }
+
+  /// Regions written as text, one id per cell: either whitespace separated
+  /// numbers, or one character per cell such as `AAABBBCCC`.
+  pub fn parse<T, N>(nd: &ConstraintListGenerator<T, N>, text: &str) -> Result<GGRegion, SudokuError>
+  where T: ConstraintList<N>, N: Note
+  {
+    let mut words: Vec<String> = text.split_whitespace().map(|word| word.to_string()).collect();
+    if words.len() != nd.width * nd.height {
+      words = text.chars().filter(|ch| !ch.is_whitespace()).map(|ch| ch.to_string()).collect();
+    }
+    let mut names = vec![];
+    let map: Vec<usize> = words.iter().map(|word| {
+      match names.iter().position(|&name| name == word) {
+        Some(id) => id,
+        None => {
+          names.push(word);
+          names.len() - 1
+        },
+      }
+    }).collect();
+    GGRegion::new(nd, &map)
+  }
+
+  /// Region of every cell.
+  pub fn map(&self) -> &[usize] {
+    &self.map
+  }
+}
+
+impl GroupGenerator for GGRegion {
+  fn get_board(&self) -> (usize, usize) {
+    (self.board_width, self.board_height)
+  }
+
+  fn get_size(&self) -> (usize, usize) {
+    (self.members.len(), self.members.first().map_or(0, |m| m.len()))
+  }
+
+  fn get_member(&self, group: usize, member: usize) -> usize {
+    self.members[group][member]
+  }
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use super::super::{
+  error::SudokuError,
+  sudoku::{
+    FLAG_IGNORED,
+    FLAG_OK,
+    Collapsed,
+    Constraint,
+    Note,
+    Rule,
+    Sudoku,
+  },
+};
+
+/// Cells holding distinct values that add up to `sum`.
+#[derive(Clone)]
+pub struct KillerCage {
+  cells: Vec<usize>,
+  sum: usize,
+}
+
+// Whether each cell can get a different value of `values`.
+pub(super) fn has_matching<N: Note>(rule: &Rule<N>, notes: &[N], values: &[usize]) -> bool {
+  fn augment<N: Note>(rule: &Rule<N>, notes: &[N], values: &[usize], cell: usize, seen: &mut [bool], owner: &mut [usize]) -> bool {
+    for (k, &value) in values.iter().enumerate() {
+      if seen[k] || notes[cell] & rule.note[value] == rule.zero {
+        continue;
+      }
+      seen[k] = true;
+      if owner[k] == usize::MAX || augment(rule, notes, values, owner[k], seen, owner) {
+        owner[k] = cell;
+        return true;
+      }
+    }
+    false
+  }
+
+  let mut owner = vec![usize::MAX; values.len()];
+  for cell in 0 .. notes.len() {
+    let mut seen = vec![false; values.len()];
+    if !augment(rule, notes, values, cell, &mut seen, &mut owner) {
+      return false;
+    }
+  }
+  true
+}
+
+// Collect in `support` the values of every combination of distinct values
+// adding up to `target` that can be spread over the cells of `notes`.
+pub(super) fn combine<N: Note>(rule: &Rule<N>, notes: &[N], available: &[usize], target: usize, chosen: &mut Vec<usize>, support: &mut N) {
+  if chosen.len() == notes.len() {
+    if target == 0 && has_matching(rule, notes, chosen) {
+      for &value in chosen.iter() {
+        *support = *support | rule.note[value];
+      }
+    }
+    return;
+  }
+  for (k, &value) in available.iter().enumerate() {
+    // Values are sorted, so larger ones can't fit either.
+    if value + 1 > target {
+      break;
+    }
+    chosen.push(value);
+    combine(rule, notes, &available[k + 1 ..], target - value - 1, chosen, support);
+    chosen.pop();
+  }
+}
+
+impl KillerCage {
+  pub fn new(cells: Vec<usize>, sum: usize) -> KillerCage {
+    KillerCage {
+      cells,
+      sum,
+    }
+  }
+
+  pub fn cells(&self) -> &[usize] {
+    &self.cells
+  }
+
+  pub fn sum(&self) -> usize {
+    self.sum
+  }
+
+  /// Attach the cage to each of its cells and register it as a group.
+  pub fn apply<N: Note>(self, dst: &mut Rule<N>) -> Result<(), SudokuError> {
+    dst.add_group(self.cells.clone())?;
+    let cells = self.cells.clone();
+    dst.add_shared_constraint(&cells, Arc::new(self))
+  }
+
+  fn prune<N: Note>(&self, sudoku: &mut Sudoku<N>) -> Collapsed {
+    let rule = sudoku.rule;
+    let mut used = rule.zero;
+    let mut total = 0;
+    let mut open = vec![];
+    for &i in self.cells.iter() {
+      if sudoku.flags[i] & FLAG_OK == FLAG_OK {
+        let note = sudoku.board[i];
+        if used & note != rule.zero {
+          return Collapsed::Error;
+        }
+        used = used | note;
+        total += rule.value(note).map_or(0, |v| v + 1);
+      } else if sudoku.flags[i] & FLAG_IGNORED != FLAG_IGNORED {
+        open.push(i);
+      }
+    }
+    if total > self.sum || (open.is_empty() && total != self.sum) {
+      return Collapsed::Error;
+    }
+    if open.is_empty() {
+      return Collapsed::Unchanged;
+    }
+
+    let notes: Vec<N> = open.iter().map(|&i| sudoku.board[i] & !used).collect();
+    let mut union = rule.zero;
+    for &note in notes.iter() {
+      union = union | note;
+    }
+    let available: Vec<usize> = (0 .. rule.note.len())
+      .filter(|&v| union & rule.note[v] != rule.zero)
+      .collect();
+    let mut support = rule.zero;
+    combine(rule, &notes, &available, self.sum - total, &mut vec![], &mut support);
+
+    let mut result = Collapsed::Unchanged;
+    for &i in open.iter() {
+      if !sudoku.restrict(i, support & !used, &mut result) {
+        return Collapsed::Error;
+      }
+    }
+    result
+  }
+}
+
+impl<N: Note> Constraint<N> for KillerCage {
+  fn added(&self, sudoku: &mut Sudoku<N>, _index: usize) {
+    self.prune(sudoku);
+  }
+
+  fn collapsed(&self, sudoku: &mut Sudoku<N>, _index: usize, _value: usize, _note: N) -> Collapsed {
+    self.prune(sudoku)
+  }
+
+  fn neighbours(&self) -> &[usize] {
+    &self.cells
+  }
+
+  fn name(&self) -> &str {
+    "killer cage"
+  }
+
+  fn conflict(&self, sudoku: &Sudoku<N>, index: usize, value: usize) -> Option<usize> {
+    let note = sudoku.rule.note[value];
+    self.cells.iter().copied().find(|&i| {
+      i != index && sudoku.flags[i] & FLAG_OK == FLAG_OK && sudoku.board[i] == note
+    })
+  }
+}
@@ -0,0 +1,32 @@
+use super::Pairwise;
+
+/// Kropki dots between related cells: a white dot requires consecutive
+/// values, a black dot requires one value to be twice the other. The negative
+/// form forbids both relations between related cells without a dot.
+pub struct Kropki;
+
+fn consecutive(a: usize, b: usize) -> bool {
+  a.abs_diff(b) == 1
+}
+
+fn ratio(a: usize, b: usize) -> bool {
+  a == 2 * b || b == 2 * a
+}
+
+impl Kropki {
+  /// Related cells hold consecutive values.
+  pub fn white() -> Pairwise {
+    Pairwise::new("white dot", consecutive)
+  }
+
+  /// Related cells hold values in a 1:2 ratio.
+  pub fn black() -> Pairwise {
+    Pairwise::new("black dot", ratio)
+  }
+
+  /// Related cells hold neither consecutive values nor values in a 1:2 ratio,
+  /// unless the pair is one of `dots`.
+  pub fn negative(dots: Vec<(usize, usize)>) -> Pairwise {
+    Pairwise::negative("kropki negative", |a, b| consecutive(a, b) || ratio(a, b), dots)
+  }
+}
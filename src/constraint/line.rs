@@ -0,0 +1,225 @@
+use std::sync::Arc;
+
+use super::{
+  g_king_move,
+  g_path,
+  killer_cage::has_matching,
+  thermometer::{fill_down, fill_up},
+  super::error::SudokuError,
+  super::sudoku::{
+    FLAG_OK,
+    Collapsed,
+    Constraint,
+    Note,
+    Rule,
+    Sudoku,
+  },
+};
+
+/// Rule followed by the cells of a [`Line`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineKind {
+  /// Cells next to each other on the line differ by at least the given amount,
+  /// 5 for German whispers and 4 for Dutch whispers.
+  Whispers(usize),
+  /// The line holds distinct consecutive values, in any order.
+  Renban,
+  /// The line reads the same from both ends.
+  Palindrome,
+}
+
+/// Constraint along a path of cells, from one end of the line to the other.
+/// Cells next to each other on the line must be a king move apart.
+#[derive(Clone)]
+pub struct Line {
+  kind: LineKind,
+  cells: Vec<usize>,
+}
+
+impl Line {
+  pub fn new(kind: LineKind, cells: Vec<usize>) -> Line {
+    Line {
+      kind,
+      cells,
+    }
+  }
+
+  /// Neighbours on the line differ by at least 5.
+  pub fn german_whispers(cells: Vec<usize>) -> Line {
+    Line::new(LineKind::Whispers(5), cells)
+  }
+
+  /// Neighbours on the line differ by at least 4.
+  pub fn dutch_whispers(cells: Vec<usize>) -> Line {
+    Line::new(LineKind::Whispers(4), cells)
+  }
+
+  /// The line holds distinct consecutive values, in any order.
+  pub fn renban(cells: Vec<usize>) -> Line {
+    Line::new(LineKind::Renban, cells)
+  }
+
+  /// Cells at the same distance from both ends hold the same value.
+  pub fn palindrome(cells: Vec<usize>) -> Line {
+    Line::new(LineKind::Palindrome, cells)
+  }
+
+  /// Line of cells starting at `start` and moving by each offset of `steps`
+  /// in turn, see [`g_path`]. None if the line leaves the board.
+  pub fn from_steps(kind: LineKind, width: usize, height: usize, start: usize, steps: &[(i32, i32)]) -> Option<Line> {
+    Some(Line::new(kind, g_path(width, height, start, steps)?))
+  }
+
+  pub fn kind(&self) -> LineKind {
+    self.kind
+  }
+
+  pub fn cells(&self) -> &[usize] {
+    &self.cells
+  }
+
+  /// Attach the line to each of its cells. A renban line is also registered
+  /// as a group.
+  pub fn apply<N: Note>(self, dst: &mut Rule<N>) -> Result<(), SudokuError> {
+    for pair in self.cells.windows(2) {
+      let (a, b) = (pair[0], pair[1]);
+      if a < dst.size && b < dst.size && !g_king_move(dst.width, dst.height, a).contains(&b) {
+        return Err(SudokuError::InvalidLine(format!(
+          "r{}c{} and r{}c{} aren't next to each other",
+          a / dst.width + 1, a % dst.width + 1, b / dst.width + 1, b % dst.width + 1
+        )));
+      }
+    }
+    if self.kind == LineKind::Renban {
+      dst.add_group(self.cells.clone())?;
+    }
+    let cells = self.cells.clone();
+    dst.add_shared_constraint(&cells, Arc::new(self))
+  }
+
+  // Values at least `diff` away from a value of `note`.
+  fn far<N: Note>(rule: &Rule<N>, note: N, diff: usize) -> N {
+    let diff = diff as i32;
+    (fill_up(rule, note) << diff | fill_down(rule, note) >> diff) & rule.all
+  }
+
+  // Each cell keeps the values far enough from a candidate of both its
+  // neighbours on the line.
+  fn prune_whispers<N: Note>(&self, sudoku: &mut Sudoku<N>, diff: usize) -> Collapsed {
+    let rule = sudoku.rule;
+    let mut result = Collapsed::Unchanged;
+    for k in 1 .. self.cells.len() {
+      let (a, b) = (self.cells[k - 1], self.cells[k]);
+      if !sudoku.restrict(b, Line::far(rule, sudoku.board[a], diff), &mut result)
+        || !sudoku.restrict(a, Line::far(rule, sudoku.board[b], diff), &mut result)
+      {
+        return Collapsed::Error;
+      }
+    }
+    result
+  }
+
+  // Keep the values of every run of consecutive values the cells can be
+  // spread over.
+  fn prune_renban<N: Note>(&self, sudoku: &mut Sudoku<N>) -> Collapsed {
+    let rule = sudoku.rule;
+    let length = self.cells.len();
+    if length > rule.note.len() {
+      return Collapsed::Error;
+    }
+    let notes: Vec<N> = self.cells.iter().map(|&i| sudoku.board[i]).collect();
+    let mut support = rule.zero;
+    for start in 0 ..= rule.note.len() - length {
+      let values: Vec<usize> = (start .. start + length).collect();
+      if has_matching(rule, &notes, &values) {
+        for &value in values.iter() {
+          support = support | rule.note[value];
+        }
+      }
+    }
+
+    let mut result = Collapsed::Unchanged;
+    for &i in self.cells.iter() {
+      if !sudoku.restrict(i, support, &mut result) {
+        return Collapsed::Error;
+      }
+    }
+    result
+  }
+
+  // Cells mirrored along the line share their candidates.
+  fn prune_palindrome<N: Note>(&self, sudoku: &mut Sudoku<N>) -> Collapsed {
+    let mut result = Collapsed::Unchanged;
+    let length = self.cells.len();
+    for k in 0 .. length / 2 {
+      let (a, b) = (self.cells[k], self.cells[length - 1 - k]);
+      let both = sudoku.board[a] & sudoku.board[b];
+      if !sudoku.restrict(a, both, &mut result) || !sudoku.restrict(b, both, &mut result) {
+        return Collapsed::Error;
+      }
+    }
+    result
+  }
+
+  fn prune<N: Note>(&self, sudoku: &mut Sudoku<N>) -> Collapsed {
+    match self.kind {
+      LineKind::Whispers(diff) => self.prune_whispers(sudoku, diff),
+      LineKind::Renban => self.prune_renban(sudoku),
+      LineKind::Palindrome => self.prune_palindrome(sudoku),
+    }
+  }
+}
+
+impl<N: Note> Constraint<N> for Line {
+  fn added(&self, sudoku: &mut Sudoku<N>, _index: usize) {
+    self.prune(sudoku);
+  }
+
+  fn collapsed(&self, sudoku: &mut Sudoku<N>, _index: usize, _value: usize, _note: N) -> Collapsed {
+    self.prune(sudoku)
+  }
+
+  fn watch_changes(&self) -> bool {
+    true
+  }
+
+  fn changed(&self, sudoku: &mut Sudoku<N>, _index: usize) -> Collapsed {
+    self.prune(sudoku)
+  }
+
+  fn neighbours(&self) -> &[usize] {
+    &self.cells
+  }
+
+  fn name(&self) -> &str {
+    match self.kind {
+      LineKind::Whispers(_) => "whispers",
+      LineKind::Renban => "renban",
+      LineKind::Palindrome => "palindrome",
+    }
+  }
+
+  fn conflict(&self, sudoku: &Sudoku<N>, index: usize, value: usize) -> Option<usize> {
+    let rule = sudoku.rule;
+    let position = self.cells.iter().position(|&i| i == index)?;
+    let given = |k: usize| {
+      let i = self.cells[k];
+      if k != position && sudoku.flags[i] & FLAG_OK == FLAG_OK {
+        rule.value(sudoku.board[i])
+      } else {
+        None
+      }
+    };
+    let length = self.cells.len();
+    let found = match self.kind {
+      LineKind::Whispers(diff) => [position.wrapping_sub(1), position + 1].into_iter()
+        .filter(|&k| k < length)
+        .find(|&k| given(k).is_some_and(|other| other.abs_diff(value) < diff)),
+      LineKind::Renban => (0 .. length)
+        .find(|&k| given(k).is_some_and(|other| other == value || other.abs_diff(value) >= length)),
+      LineKind::Palindrome => Some(length - 1 - position)
+        .filter(|&k| given(k).is_some_and(|other| other != value)),
+    };
+    found.map(|k| self.cells[k])
+  }
+}
@@ -1,27 +1,36 @@
-#![allow(dead_code)]
+use std::{fmt::Display, marker::PhantomData, sync::Arc};
 
-use std::{fmt::Display, marker::PhantomData};
-
-use super::{super::sudoku::{
+use super::{super::error::SudokuError, super::sudoku::{
   Constraint,
   Note,
   Rule,
 }, generator::{
+  GGAntiDiagonal,
   GGBlock,
+  GGDiagonal,
   GGHorizontal,
   GGVertical,
+  GGWindow,
 }};
 
+/// Describe a family of groups of the same size on a board.
 pub trait GroupGenerator {
+  /// Board width and height.
   fn get_board(&self) -> (usize, usize);
+  /// Number of groups and number of members in each group.
   fn get_size(&self) -> (usize, usize);
+  /// Cell index of a member of a group.
   fn get_member(&self, group: usize, member: usize) -> usize;
 }
 
+/// Constraint relating a cell to a list of other cells.
 pub trait ConstraintList<N>: Constraint<N> + Clone {
+  /// Relate the cell to the cell at `index`.
   fn add(&mut self, index: usize);
+  /// Whether the cell is related to the cell at `index`.
   fn contain(&self, index: usize) -> bool;
 
+  /// Like [`ConstraintList::add`], but skip cells already related.
   fn add_unique(&mut self, index: usize) {
     if !self.contain(index) {
       self.add(index)
@@ -29,6 +38,8 @@ pub trait ConstraintList<N>: Constraint<N> + Clone {
   }
 }
 
+/// Build one [`ConstraintList`] per cell from groups and neighbourhoods,
+/// then attach them to a [`Rule`] with [`ConstraintListGenerator::apply`].
 pub struct ConstraintListGenerator<T, N>
 where
   T: ConstraintList<N>,
@@ -37,6 +48,7 @@ where
   pub width: usize,
   pub height: usize,
   constraints: Vec<T>,
+  groups: Vec<Vec<usize>>,
   phantom: PhantomData<N>
 }
 
@@ -45,22 +57,39 @@ where
   T: ConstraintList<N>,
   N: Note
 {
+  /// Start every cell with a copy of `one`.
   pub fn new(one: T, rule: &Rule<N>) -> ConstraintListGenerator<T, N> {
     ConstraintListGenerator::<T, N> {
       width: rule.width,
       height: rule.height,
       constraints: vec![one; rule.size],
+      groups: vec![],
       phantom: PhantomData,
     }
   }
 
-  pub fn apply<'a>(&'a self, dst: &mut Rule<'a, N>) {
+  /// Attach the constraints to `dst`. Groups of distinct constraints are
+  /// also registered with [`Rule::add_group`].
+  pub fn apply(&self, dst: &mut Rule<N>) -> Result<(), SudokuError>
+  where T: 'static
+  {
     for (i, constr) in self.constraints.iter().enumerate() {
-      dst.add_constraint(i, constr);
+      dst.add_constraint(i, Arc::new(constr.clone()))?;
+    }
+    if self.constraints.first().is_some_and(|c| c.distinct()) {
+      for group in self.groups.iter() {
+        dst.add_group(group.clone())?;
+      }
     }
+    Ok(())
   }
 
-  pub fn add_group(&mut self, group: &Vec<usize>) {
+  /// Relate every cell of `group` to each other.
+  pub fn add_group(&mut self, group: &[usize]) -> Result<(), SudokuError> {
+    let size = self.constraints.len();
+    if let Some(&index) = group.iter().find(|&&i| i >= size) {
+      return Err(SudokuError::InvalidIndex { index, size });
+    }
     for &a in group.iter() {
       for &b in group.iter() {
         if a != b {
@@ -69,8 +98,24 @@ where
         }
       }
     }
+    self.groups.push(group.to_vec());
+    Ok(())
+  }
+
+  /// Relate the cells `a` and `b` to each other, such as the two cells of a
+  /// dot. The pair isn't registered as a group.
+  pub fn add_pair(&mut self, a: usize, b: usize) -> Result<(), SudokuError> {
+    let size = self.constraints.len();
+    if let Some(index) = [a, b].into_iter().find(|&i| i >= size) {
+      return Err(SudokuError::InvalidIndex { index, size });
+    }
+    self.constraints[a].add_unique(b);
+    self.constraints[b].add_unique(a);
+    Ok(())
   }
 
+  /// Relate every cell to the cells returned by `func`, such as
+  /// [`g_king_move`].
   pub fn add_every(&mut self, func: fn (width: usize, height: usize, i: usize) -> Vec<usize>) {
     for i in 0 .. (self.width * self.height) {
       let list = func(self.width, self.height, i);
@@ -80,34 +125,53 @@ where
     }
   }
 
-  pub fn add_group_generator(&mut self, gen: &dyn GroupGenerator) {
+  /// Add every group of `gen`.
+  pub fn add_group_generator(&mut self, gen: &dyn GroupGenerator) -> Result<(), SudokuError> {
     let (group_count, member_count) = gen.get_size();
     let mut group = vec![];
     for group_index in 0 .. group_count {
       for member in 0 .. member_count {
         group.push(gen.get_member(group_index, member));
       }
-      self.add_group(&group);
+      self.add_group(&group)?;
       group.clear();
     }
+    Ok(())
   }
 
-  pub fn add_vertical_group(&mut self, width: usize, height: usize, dx: usize, dy: usize) {
-    self.add_group_generator(&GGVertical::new(&self, width, height, dx, dy))
+  /// Add the columns of an area, see [`GGVertical`].
+  pub fn add_vertical_group(&mut self, width: usize, height: usize, dx: usize, dy: usize) -> Result<(), SudokuError> {
+    self.add_group_generator(&GGVertical::new(self, width, height, dx, dy))
   }
 
-  pub fn add_horizontal_group(&mut self, width: usize, height: usize, dx: usize, dy: usize) {
-    self.add_group_generator(&GGHorizontal::new(&self, width, height, dx, dy))
+  /// Add the rows of an area, see [`GGHorizontal`].
+  pub fn add_horizontal_group(&mut self, width: usize, height: usize, dx: usize, dy: usize) -> Result<(), SudokuError> {
+    self.add_group_generator(&GGHorizontal::new(self, width, height, dx, dy))
   }
 
-  pub fn add_block_group(&mut self, width: usize, height: usize, block_width: usize, block_height: usize, dx: usize, dy: usize) {
-    self.add_group_generator(&GGBlock::new(&self, width, height, block_width, block_height, dx, dy))
+  /// Add the boxes of an area, see [`GGBlock`].
+  pub fn add_block_group(&mut self, width: usize, height: usize, block_width: usize, block_height: usize, dx: usize, dy: usize) -> Result<(), SudokuError> {
+    self.add_group_generator(&GGBlock::new(self, width, height, block_width, block_height, dx, dy))
   }
 
-  pub fn add_standard_group(&mut self, width: usize, height: usize, block_width: usize, block_height: usize, dx: usize, dy: usize) {
-    self.add_vertical_group(width * block_width, height * block_height, dx, dy);
-    self.add_horizontal_group(width * block_width, height * block_height, dx, dy);
-    self.add_block_group(width, height, block_width, block_height, dx, dy);
+  /// Add the hyper sudoku windows of an area, see [`GGWindow`].
+  pub fn add_window_group(&mut self, width: usize, height: usize, block_width: usize, block_height: usize, dx: usize, dy: usize) -> Result<(), SudokuError> {
+    self.add_group_generator(&GGWindow::new(self, width, height, block_width, block_height, dx, dy))
+  }
+
+  /// Add both diagonals of a `size` x `size` area, see [`GGDiagonal`] and
+  /// [`GGAntiDiagonal`].
+  pub fn add_diagonal_group(&mut self, size: usize, dx: usize, dy: usize) -> Result<(), SudokuError> {
+    self.add_group_generator(&GGDiagonal::new(self, size, dx, dy)?)?;
+    self.add_group_generator(&GGAntiDiagonal::new(self, size, dx, dy)?)
+  }
+
+  /// Add rows, columns and boxes of a standard sudoku with `width` x `height`
+  /// boxes of `block_width` x `block_height` cells.
+  pub fn add_standard_group(&mut self, width: usize, height: usize, block_width: usize, block_height: usize, dx: usize, dy: usize) -> Result<(), SudokuError> {
+    self.add_vertical_group(width * block_width, height * block_height, dx, dy)?;
+    self.add_horizontal_group(width * block_width, height * block_height, dx, dy)?;
+    self.add_block_group(width, height, block_width, block_height, dx, dy)
   }
 
 }
@@ -124,7 +188,7 @@ impl Display for dyn GroupGenerator {
       }
     }
 
-    let print_width: usize = (group_count.ilog10() + 1).try_into().unwrap();
+    let print_width: usize = (group_count.max(1).ilog10() + 1).try_into().unwrap();
     for y in 0 .. board_height {
       for x in 0 .. board_width {
         if x > 0 {
@@ -142,6 +206,7 @@ impl Display for dyn GroupGenerator {
   }
 }
 
+/// Cell at offset (`dx`, `dy`) from cell `i`, or None outside the board.
 #[inline]
 pub fn get_cell(width: usize, height: usize, i: usize, dx: i32, dy: i32) -> Option<usize> {
   let mut x = i % width;
@@ -175,6 +240,7 @@ pub fn get_cell(width: usize, height: usize, i: usize, dx: i32, dy: i32) -> Opti
   Some(x + y * width)
 }
 
+/// Cells at each offset of `list` from cell `i` that lie on the board.
 #[inline]
 pub fn g_generate_neighbour(width: usize, height: usize, i: usize, list: Vec<(i32, i32)>) -> Vec<usize> {
   let mut result = vec![];
@@ -186,6 +252,19 @@ pub fn g_generate_neighbour(width: usize, height: usize, i: usize, list: Vec<(i3
   result
 }
 
+/// Cells of a path starting at cell `i` and moving by each offset of `steps`
+/// in turn, or None if the path leaves the board.
+pub fn g_path(width: usize, height: usize, i: usize, steps: &[(i32, i32)]) -> Option<Vec<usize>> {
+  let mut result = vec![i];
+  let mut current = i;
+  for &(dx, dy) in steps {
+    current = get_cell(width, height, current, dx, dy)?;
+    result.push(current);
+  }
+  Some(result)
+}
+
+/// Orthogonally adjacent cells.
 pub fn g_adjacent(width: usize, height: usize, i: usize) -> Vec<usize> {
   g_generate_neighbour(width, height, i, vec![
     ( 0,  1),
@@ -195,6 +274,7 @@ pub fn g_adjacent(width: usize, height: usize, i: usize) -> Vec<usize> {
   ])
 }
 
+/// Diagonally adjacent cells.
 pub fn g_diagonal(width: usize, height: usize, i: usize) -> Vec<usize> {
   g_generate_neighbour(width, height, i, vec![
     ( 1,  1),
@@ -204,6 +284,7 @@ pub fn g_diagonal(width: usize, height: usize, i: usize) -> Vec<usize> {
   ])
 }
 
+/// Cells a chess king move away.
 pub fn g_king_move(width: usize, height: usize, i: usize) -> Vec<usize> {
   g_generate_neighbour(width, height, i, vec![
     ( 0,  1),
@@ -217,6 +298,7 @@ pub fn g_king_move(width: usize, height: usize, i: usize) -> Vec<usize> {
   ])
 }
 
+/// Cells a chess knight move away.
 pub fn g_horse_move(width: usize, height: usize, i: usize) -> Vec<usize> {
   g_generate_neighbour(width, height, i, vec![
     ( 1,  2), ( 2,  1),
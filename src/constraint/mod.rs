@@ -1,6 +1,14 @@
 mod list_generator;
 mod no_duplicate;
 mod no_consecutive;
+mod killer_cage;
+mod thermometer;
+mod arrow;
+mod pairwise;
+mod kropki;
+mod xv;
+mod sandwich;
+mod line;
 mod generator;
 
 pub use list_generator::{
@@ -12,14 +20,27 @@ pub use list_generator::{
   g_generate_neighbour,
   g_horse_move,
   g_king_move,
+  g_path,
   get_cell
 };
 
 pub use no_duplicate::NoDuplicate;
 pub use no_consecutive::NoConsecutive;
+pub use killer_cage::KillerCage;
+pub use thermometer::Thermometer;
+pub use arrow::Arrow;
+pub use pairwise::Pairwise;
+pub use kropki::Kropki;
+pub use xv::Xv;
+pub use sandwich::Sandwich;
+pub use line::{Line, LineKind};
 
 pub use generator::{
   GGHorizontal,
   GGVertical,
   GGBlock,
+  GGWindow,
+  GGDiagonal,
+  GGAntiDiagonal,
+  GGRegion,
 };
@@ -3,6 +3,7 @@ use super::{
   ConstraintList,
   super::sudoku::{
     FLAG_NONE,
+    FLAG_OK,
     Collapsed,
     Constraint,
     Note,
@@ -10,7 +11,8 @@ use super::{
   }
 };
 
-#[derive(Clone)]
+/// Forbid related cells from holding consecutive values.
+#[derive(Clone, Default)]
 pub struct NoConsecutive {
   neighbours: Vec<usize>,
 }
@@ -35,7 +37,7 @@ impl<N: Note> ConstraintList<N> for NoConsecutive {
 }
 
 impl<N: Note> Constraint<N> for NoConsecutive {
-  fn added(&self, _sudoku: &mut Sudoku<N>, _index: usize) -> () {
+  fn added(&self, _sudoku: &mut Sudoku<N>, _index: usize) {
   }
 
   fn collapsed(&self, sudoku: &mut Sudoku<N>, _index: usize, _value: usize, note: N) -> Collapsed {
@@ -54,9 +56,26 @@ impl<N: Note> Constraint<N> for NoConsecutive {
       if new_value != old_value {
         result = Collapsed::Ok;
       }
-      sudoku.board[neighbour] = new_value;
+      sudoku.set_note(neighbour, new_value);
     }
 
     result
   }
+
+  fn neighbours(&self) -> &[usize] {
+    &self.neighbours
+  }
+
+  fn name(&self) -> &str {
+    "no consecutive"
+  }
+
+  fn conflict(&self, sudoku: &Sudoku<N>, _index: usize, value: usize) -> Option<usize> {
+    let rule = sudoku.rule;
+    let note = rule.note[value];
+    let forbidden = (note << 1 | note >> 1) & rule.all;
+    self.neighbours.iter().copied().find(|&i| {
+      sudoku.flags[i] & FLAG_OK == FLAG_OK && sudoku.board[i] & forbidden != rule.zero
+    })
+  }
 }
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use super::{
+  ConstraintList,
+  super::error::SudokuError,
+  super::sudoku::{
+    FLAG_OK,
+    Collapsed,
+    Constraint,
+    Note,
+    Rule,
+    Sudoku,
+  }
+};
+
+/// Relation between the values of related cells, given by `related` on two
+/// values. The negative form forbids the relation between related cells,
+/// unless the pair is marked.
+#[derive(Clone)]
+pub struct Pairwise {
+  name: &'static str,
+  related: fn(usize, usize) -> bool,
+  // Marked pairs, which the negative constraint skips.
+  marks: Option<Arc<Vec<(usize, usize)>>>,
+  neighbours: Vec<usize>,
+}
+
+impl Pairwise {
+  /// Related cells hold values for which `related` holds.
+  pub fn new(name: &'static str, related: fn(usize, usize) -> bool) -> Pairwise {
+    Pairwise {
+      name,
+      related,
+      marks: None,
+      neighbours: vec![],
+    }
+  }
+
+  /// Related cells hold values for which `related` doesn't hold, unless the
+  /// pair is one of `marks`.
+  pub fn negative(name: &'static str, related: fn(usize, usize) -> bool, marks: Vec<(usize, usize)>) -> Pairwise {
+    Pairwise {
+      name,
+      related,
+      marks: Some(Arc::new(marks)),
+      neighbours: vec![],
+    }
+  }
+
+  /// Attach the relation between `a` and `b` alone to `dst`.
+  pub fn apply_pair<N: Note>(self, dst: &mut Rule<N>, a: usize, b: usize) -> Result<(), SudokuError> {
+    let mut first = self.clone();
+    first.neighbours.push(b);
+    let mut second = self;
+    second.neighbours.push(a);
+    dst.add_constraint(a, Arc::new(first))?;
+    dst.add_constraint(b, Arc::new(second))
+  }
+
+  fn is_negative(&self) -> bool {
+    self.marks.is_some()
+  }
+
+  fn has_mark(&self, a: usize, b: usize) -> bool {
+    match &self.marks {
+      Some(marks) => marks.iter().any(|&pair| pair == (a, b) || pair == (b, a)),
+      None => false,
+    }
+  }
+
+  // Candidates a neighbour may keep next to a cell holding `note`.
+  fn allowed<N: Note>(&self, rule: &Rule<N>, note: N) -> N {
+    let values: Vec<usize> = (0 .. rule.note.len()).filter(|&v| note & rule.note[v] != rule.zero).collect();
+    let mut result = rule.zero;
+    for (w, &bit) in rule.note.iter().enumerate() {
+      if values.iter().any(|&v| (self.related)(v + 1, w + 1) != self.is_negative()) {
+        result = result | bit;
+      }
+    }
+    result
+  }
+
+  // Restrict every neighbour to the candidates matching `note`.
+  fn prune<N: Note>(&self, sudoku: &mut Sudoku<N>, index: usize, note: N) -> Collapsed {
+    let rule = sudoku.rule;
+    let allowed = self.allowed(rule, note);
+    let mut result = Collapsed::Unchanged;
+    for &neighbour in self.neighbours.iter() {
+      if self.has_mark(index, neighbour) {
+        continue;
+      }
+      if !sudoku.restrict(neighbour, allowed, &mut result) {
+        return Collapsed::Error;
+      }
+    }
+    result
+  }
+}
+
+impl<N: Note> ConstraintList<N> for Pairwise {
+  fn add(&mut self, index: usize) {
+    self.neighbours.push(index)
+  }
+
+  fn contain(&self, index: usize) -> bool {
+    self.neighbours.contains(&index)
+  }
+}
+
+impl<N: Note> Constraint<N> for Pairwise {
+  fn added(&self, sudoku: &mut Sudoku<N>, index: usize) {
+    if !self.is_negative() {
+      self.prune(sudoku, index, sudoku.board[index]);
+    }
+  }
+
+  fn collapsed(&self, sudoku: &mut Sudoku<N>, index: usize, _value: usize, note: N) -> Collapsed {
+    self.prune(sudoku, index, note)
+  }
+
+  // A relation also restricts neighbours of unsolved cells; the negative form
+  // only matters once a value is known.
+  fn watch_changes(&self) -> bool {
+    !self.is_negative()
+  }
+
+  fn changed(&self, sudoku: &mut Sudoku<N>, index: usize) -> Collapsed {
+    self.prune(sudoku, index, sudoku.board[index])
+  }
+
+  fn neighbours(&self) -> &[usize] {
+    &self.neighbours
+  }
+
+  fn name(&self) -> &str {
+    self.name
+  }
+
+  fn conflict(&self, sudoku: &Sudoku<N>, index: usize, value: usize) -> Option<usize> {
+    let rule = sudoku.rule;
+    let allowed = self.allowed(rule, rule.note[value]);
+    self.neighbours.iter().copied().find(|&i| {
+      !self.has_mark(index, i)
+        && sudoku.flags[i] & FLAG_OK == FLAG_OK
+        && sudoku.board[i] & allowed == rule.zero
+    })
+  }
+}
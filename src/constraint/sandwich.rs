@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use super::{
+  GroupGenerator,
+  killer_cage::combine,
+  super::error::SudokuError,
+  super::sudoku::{
+    FLAG_OK,
+    Collapsed,
+    Constraint,
+    Note,
+    Rule,
+    Sudoku,
+  },
+};
+
+/// The values between the smallest and the largest value of a line add up to
+/// `sum`. The line is expected to hold every value once, like the rows and
+/// columns of a sudoku.
+#[derive(Clone)]
+pub struct Sandwich {
+  cells: Vec<usize>,
+  sum: usize,
+}
+
+impl Sandwich {
+  pub fn new(cells: Vec<usize>, sum: usize) -> Sandwich {
+    Sandwich {
+      cells,
+      sum,
+    }
+  }
+
+  /// Sandwich on the group `group` of `gen`, such as a row of
+  /// [`super::GGHorizontal`] or a column of [`super::GGVertical`].
+  pub fn from_group(gen: &dyn GroupGenerator, group: usize, sum: usize) -> Sandwich {
+    let (_, member_count) = gen.get_size();
+    let cells = (0 .. member_count).map(|member| gen.get_member(group, member)).collect();
+    Sandwich::new(cells, sum)
+  }
+
+  pub fn cells(&self) -> &[usize] {
+    &self.cells
+  }
+
+  pub fn sum(&self) -> usize {
+    self.sum
+  }
+
+  /// Attach the sandwich to each cell of the line.
+  pub fn apply<N: Note>(self, dst: &mut Rule<N>) -> Result<(), SudokuError> {
+    let cells = self.cells.clone();
+    dst.add_shared_constraint(&cells, Arc::new(self))
+  }
+
+  // Try every position of the smallest and largest value, keeping the
+  // candidates of the placements whose crust can be filled.
+  fn prune<N: Note>(&self, sudoku: &mut Sudoku<N>) -> Collapsed {
+    let rule = sudoku.rule;
+    let low = rule.note[0];
+    let high = rule.note[rule.note.len() - 1];
+    let inner = rule.all & !low & !high;
+    let available: Vec<usize> = (1 .. rule.note.len() - 1).collect();
+    let notes: Vec<N> = self.cells.iter().map(|&i| sudoku.board[i]).collect();
+    let mut support = vec![rule.zero; notes.len()];
+
+    for (p, &low_note) in notes.iter().enumerate() {
+      if low_note & low == rule.zero {
+        continue;
+      }
+      for (q, &high_note) in notes.iter().enumerate() {
+        if q == p || high_note & high == rule.zero {
+          continue;
+        }
+        let fits = notes.iter().enumerate().all(|(k, &note)| k == p || k == q || note & inner != rule.zero);
+        if !fits {
+          continue;
+        }
+        let (a, b) = (p.min(q), p.max(q));
+        let between: Vec<N> = notes[a + 1 .. b].iter().map(|&note| note & inner).collect();
+        let mut values = rule.zero;
+        combine(rule, &between, &available, self.sum, &mut vec![], &mut values);
+        if values == rule.zero && !(between.is_empty() && self.sum == 0) {
+          continue;
+        }
+        support[p] = support[p] | low;
+        support[q] = support[q] | high;
+        for (k, &note) in notes.iter().enumerate() {
+          if k == p || k == q {
+            continue;
+          }
+          let allowed = if a < k && k < b { values } else { inner };
+          support[k] = support[k] | (note & allowed);
+        }
+      }
+    }
+
+    let mut result = Collapsed::Unchanged;
+    for (&i, &allowed) in self.cells.iter().zip(support.iter()) {
+      if !sudoku.restrict(i, allowed, &mut result) {
+        return Collapsed::Error;
+      }
+    }
+    result
+  }
+}
+
+impl<N: Note> Constraint<N> for Sandwich {
+  fn added(&self, sudoku: &mut Sudoku<N>, _index: usize) {
+    self.prune(sudoku);
+  }
+
+  fn collapsed(&self, sudoku: &mut Sudoku<N>, _index: usize, _value: usize, _note: N) -> Collapsed {
+    self.prune(sudoku)
+  }
+
+  fn watch_changes(&self) -> bool {
+    true
+  }
+
+  fn changed(&self, sudoku: &mut Sudoku<N>, _index: usize) -> Collapsed {
+    self.prune(sudoku)
+  }
+
+  fn neighbours(&self) -> &[usize] {
+    &self.cells
+  }
+
+  fn name(&self) -> &str {
+    "sandwich"
+  }
+
+  // Givens between the given smallest and largest values adding up to more
+  // than the sum, or to another sum once all of them are given.
+  fn conflict(&self, sudoku: &Sudoku<N>, index: usize, value: usize) -> Option<usize> {
+    let rule = sudoku.rule;
+    let given: Vec<Option<usize>> = self.cells.iter().map(|&i| {
+      if i == index {
+        Some(value)
+      } else if sudoku.flags[i] & FLAG_OK == FLAG_OK {
+        rule.value(sudoku.board[i])
+      } else {
+        None
+      }
+    }).collect();
+    let p = given.iter().position(|&v| v == Some(0))?;
+    let q = given.iter().position(|&v| v == Some(rule.note.len() - 1))?;
+    let (a, b) = (p.min(q), p.max(q));
+    let mut total = 0;
+    let mut complete = true;
+    for v in given[a + 1 .. b].iter() {
+      match v {
+        Some(v) => total += v + 1,
+        None => complete = false,
+      }
+    }
+    if total > self.sum || (complete && total != self.sum) {
+      (a ..= b).find(|&k| self.cells[k] != index && given[k].is_some()).map(|k| self.cells[k])
+    } else {
+      None
+    }
+  }
+}
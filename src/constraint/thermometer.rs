@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use super::super::{
+  error::SudokuError,
+  sudoku::{
+    FLAG_OK,
+    Collapsed,
+    Constraint,
+    Note,
+    Rule,
+    Sudoku,
+  },
+};
+
+/// Values strictly increasing along `cells`, from the bulb to the tip.
+#[derive(Clone)]
+pub struct Thermometer {
+  cells: Vec<usize>,
+}
+
+// Every value greater than or equal to the smallest candidate of `note`.
+pub(super) fn fill_up<N: Note>(rule: &Rule<N>, note: N) -> N {
+  let mut result = note;
+  for _ in 1 .. rule.note.len() {
+    result = result | result << 1;
+  }
+  result & rule.all
+}
+
+// Every value less than or equal to the largest candidate of `note`.
+pub(super) fn fill_down<N: Note>(rule: &Rule<N>, note: N) -> N {
+  let mut result = note;
+  for _ in 1 .. rule.note.len() {
+    result = result | result >> 1;
+  }
+  result
+}
+
+impl Thermometer {
+  pub fn new(cells: Vec<usize>) -> Thermometer {
+    Thermometer {
+      cells,
+    }
+  }
+
+  pub fn cells(&self) -> &[usize] {
+    &self.cells
+  }
+
+  /// Attach the thermometer to each of its cells and register it as a group.
+  pub fn apply<N: Note>(self, dst: &mut Rule<N>) -> Result<(), SudokuError> {
+    dst.add_group(self.cells.clone())?;
+    let cells = self.cells.clone();
+    dst.add_shared_constraint(&cells, Arc::new(self))
+  }
+
+  // Each cell must be above the smallest candidate of the previous cell and
+  // below the largest candidate of the next one.
+  fn prune<N: Note>(&self, sudoku: &mut Sudoku<N>) -> Collapsed {
+    let rule = sudoku.rule;
+    let mut result = Collapsed::Unchanged;
+    for k in 1 .. self.cells.len() {
+      let allowed = fill_up(rule, sudoku.board[self.cells[k - 1]]) << 1;
+      if !sudoku.restrict(self.cells[k], allowed, &mut result) {
+        return Collapsed::Error;
+      }
+    }
+    for k in (1 .. self.cells.len()).rev() {
+      let allowed = fill_down(rule, sudoku.board[self.cells[k]]) >> 1;
+      if !sudoku.restrict(self.cells[k - 1], allowed, &mut result) {
+        return Collapsed::Error;
+      }
+    }
+    result
+  }
+}
+
+impl<N: Note> Constraint<N> for Thermometer {
+  fn added(&self, sudoku: &mut Sudoku<N>, _index: usize) {
+    self.prune(sudoku);
+  }
+
+  fn collapsed(&self, sudoku: &mut Sudoku<N>, _index: usize, _value: usize, _note: N) -> Collapsed {
+    self.prune(sudoku)
+  }
+
+  fn watch_changes(&self) -> bool {
+    true
+  }
+
+  fn changed(&self, sudoku: &mut Sudoku<N>, _index: usize) -> Collapsed {
+    self.prune(sudoku)
+  }
+
+  fn neighbours(&self) -> &[usize] {
+    &self.cells
+  }
+
+  fn name(&self) -> &str {
+    "thermometer"
+  }
+
+  // A given too close in value to another given for the cells between them.
+  fn conflict(&self, sudoku: &Sudoku<N>, index: usize, value: usize) -> Option<usize> {
+    let rule = sudoku.rule;
+    let position = self.cells.iter().position(|&i| i == index)?;
+    self.cells.iter().enumerate().find_map(|(k, &i)| {
+      if k == position || sudoku.flags[i] & FLAG_OK != FLAG_OK {
+        return None;
+      }
+      let other = rule.value(sudoku.board[i])?;
+      let ok = if k < position {
+        other + (position - k) <= value
+      } else {
+        value + (k - position) <= other
+      };
+      if ok { None } else { Some(i) }
+    })
+  }
+}
@@ -0,0 +1,23 @@
+use super::Pairwise;
+
+/// XV marks between related cells: the two values sum to 5 (V) or 10 (X).
+/// The negative form forbids both sums between related cells without a mark.
+pub struct Xv;
+
+impl Xv {
+  /// Related cells sum to 5.
+  pub fn v() -> Pairwise {
+    Pairwise::new("v", |a, b| a + b == 5)
+  }
+
+  /// Related cells sum to 10.
+  pub fn x() -> Pairwise {
+    Pairwise::new("x", |a, b| a + b == 10)
+  }
+
+  /// Related cells sum to neither 5 nor 10, unless the pair is one of
+  /// `marks`.
+  pub fn negative(marks: Vec<(usize, usize)>) -> Pairwise {
+    Pairwise::negative("xv negative", |a, b| a + b == 5 || a + b == 10, marks)
+  }
+}
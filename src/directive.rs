@@ -0,0 +1,147 @@
+use super::{
+  constraint::{Arrow, ConstraintListGenerator, KillerCage, Kropki, Line, NoDuplicate, Sandwich, Thermometer, Xv},
+  error::SudokuError,
+  sudoku::{Note, Rule},
+};
+
+// Puzzle text may hold directive lines, starting with `!`, which add
+// variant constraints to the rule before the grid is read, such as
+//
+//   !thermo r1c1 r1c2 r1c3
+//   !killer 15 r1c1 r1c2 r2c1
+//   !arrow r1c1 r1c2 r1c3
+//   !white r1c1 r1c2
+//   !black r1c1 r2c1
+//   !x r1c1 r1c2
+//   !v r1c1 r2c1
+//   !sandwich r1 12
+//   !german r1c1 r1c2 r2c2
+//   !group r1c1 r2c2 r3c3
+//
+// Cells are written as `rXcY`, rows as `rX` and columns as `cY`, 1-based.
+
+/// Whether a line of puzzle text is a directive.
+pub fn is_directive(line: &str) -> bool {
+  line.trim_start().starts_with('!')
+}
+
+/// Parse a cell written as `rXcY` (1-based). Cells outside of every grid
+/// aren't accepted.
+pub fn parse_cell<T: Note>(rule: &Rule<T>, text: &str) -> Option<usize> {
+  let text = text.to_ascii_lowercase();
+  let (row, column) = text.strip_prefix('r')?.split_once('c')?;
+  let row = row.parse::<usize>().ok()?;
+  let column = column.parse::<usize>().ok()?;
+  if row == 0 || column == 0 || row > rule.height || column > rule.width {
+    return None;
+  }
+  let index = (row - 1) * rule.width + column - 1;
+  if rule.is_ignored(index) { None } else { Some(index) }
+}
+
+// Split a line into words along with their 1-based column.
+pub(crate) fn words(line: &str) -> Vec<(usize, &str)> {
+  let mut result = vec![];
+  let mut start = None;
+  for (i, ch) in line.char_indices() {
+    match (ch.is_whitespace(), start) {
+      (true, Some(s)) => {
+        result.push((s, &line[s .. i]));
+        start = None;
+      },
+      (false, None) => start = Some(i),
+      _ => {},
+    }
+  }
+  if let Some(s) = start {
+    result.push((s, &line[s ..]));
+  }
+  result.into_iter().map(|(s, word)| (line[.. s].chars().count() + 1, word)).collect()
+}
+
+// Cells of a row written as `rX` or a column written as `cY`. The line must
+// hold every value once, so rows and columns spanning several grids aren't
+// accepted.
+fn parse_line<T: Note>(rule: &Rule<T>, text: &str) -> Option<Vec<usize>> {
+  let text = text.to_ascii_lowercase();
+  let cells: Vec<usize> = if let Some(row) = text.strip_prefix('r') {
+    let row = row.parse::<usize>().ok().filter(|&r| r >= 1 && r <= rule.height)?;
+    ((row - 1) * rule.width .. row * rule.width).collect()
+  } else {
+    let column = text.strip_prefix('c')?.parse::<usize>().ok().filter(|&c| c >= 1 && c <= rule.width)?;
+    (0 .. rule.height).map(|y| y * rule.width + column - 1).collect()
+  };
+  if cells.len() != rule.note.len() || cells.iter().any(|&i| rule.is_ignored(i)) {
+    return None;
+  }
+  Some(cells)
+}
+
+fn parse_cells<T: Note>(rule: &Rule<T>, line: usize, list: &[(usize, &str)]) -> Result<Vec<usize>, SudokuError> {
+  list.iter().map(|&(column, word)| {
+    parse_cell(rule, word).ok_or(SudokuError::Parse { line, column, text: word.to_string() })
+  }).collect()
+}
+
+/// Apply a single directive, without the leading `!`, found at `line`.
+/// Columns of errors are counted from the start of `text`.
+pub fn apply_directive<T: Note>(rule: &mut Rule<T>, line: usize, text: &str) -> Result<(), SudokuError> {
+  let list = words(text);
+  let (column, name) = match list.first() {
+    Some(&first) => first,
+    None => return Ok(()),
+  };
+  match name {
+    "thermo" => {
+      let cells = parse_cells(rule, line, &list[1 ..])?;
+      Thermometer::new(cells).apply(rule)
+    },
+    "killer" if list.len() > 2 => {
+      let (column, word) = list[1];
+      let sum = word.parse::<usize>().map_err(|_| SudokuError::Parse { line, column, text: word.to_string() })?;
+      let cells = parse_cells(rule, line, &list[2 ..])?;
+      KillerCage::new(cells, sum).apply(rule)
+    },
+    "arrow" if list.len() > 2 => {
+      let cells = parse_cells(rule, line, &list[1 ..])?;
+      Arrow::new(cells[0], cells[1 ..].to_vec()).apply(rule)
+    },
+    "white" | "black" if list.len() == 3 => {
+      let cells = parse_cells(rule, line, &list[1 ..])?;
+      let dot = if name == "white" { Kropki::white() } else { Kropki::black() };
+      dot.apply_pair(rule, cells[0], cells[1])
+    },
+    "x" | "v" if list.len() == 3 => {
+      let cells = parse_cells(rule, line, &list[1 ..])?;
+      let mark = if name == "x" { Xv::x() } else { Xv::v() };
+      mark.apply_pair(rule, cells[0], cells[1])
+    },
+    "german" | "dutch" | "renban" | "palindrome" => {
+      let cells = parse_cells(rule, line, &list[1 ..])?;
+      match name {
+        "german" => Line::german_whispers(cells),
+        "dutch" => Line::dutch_whispers(cells),
+        "renban" => Line::renban(cells),
+        _ => Line::palindrome(cells),
+      }.apply(rule)
+    },
+    "group" if list.len() > 1 => {
+      // A group holds each value once at most.
+      if let Some(&(column, word)) = list.get(rule.note.len() + 1) {
+        return Err(SudokuError::Parse { line, column, text: word.to_string() });
+      }
+      let cells = parse_cells(rule, line, &list[1 ..])?;
+      let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), rule);
+      nodup.add_group(&cells)?;
+      nodup.apply(rule)
+    },
+    "sandwich" if list.len() == 3 => {
+      let (column, word) = list[1];
+      let cells = parse_line(rule, word).ok_or(SudokuError::Parse { line, column, text: word.to_string() })?;
+      let (column, word) = list[2];
+      let sum = word.parse::<usize>().map_err(|_| SudokuError::Parse { line, column, text: word.to_string() })?;
+      Sandwich::new(cells, sum).apply(rule)
+    },
+    _ => Err(SudokuError::Parse { line, column, text: name.to_string() }),
+  }
+}
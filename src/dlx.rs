@@ -0,0 +1,270 @@
+use super::{error::SudokuError, sudoku::{FLAG_IGNORED, FLAG_OK, Note, Sudoku}};
+
+/// Exact cover solver (Knuth's Algorithm X with dancing links) for rules made
+/// only of no-duplicate constraints.
+///
+/// Every candidate (cell, value) is a row. Each cell has to be filled exactly
+/// once, each value has to appear exactly once in a full group, and at most
+/// once in smaller groups or between neighbours sharing no group.
+pub struct Dlx<'a, T> {
+  sudoku: Sudoku<'a, T>,
+  left: Vec<usize>,
+  right: Vec<usize>,
+  up: Vec<usize>,
+  down: Vec<usize>,
+  column: Vec<usize>,
+  row: Vec<usize>,
+  column_size: Vec<usize>,
+  candidates: Vec<(usize, usize)>,
+  stack: Vec<usize>,
+}
+
+// Node 0 is the root; column headers follow it.
+const ROOT: usize = 0;
+
+impl<'a, T: Note> Dlx<'a, T> {
+
+  /// Build the matrix from the current candidates of `sudoku`. Fail when the
+  /// rule contains a constraint this backend can't express.
+  pub fn new(sudoku: &Sudoku<'a, T>) -> Result<Dlx<'a, T>, SudokuError> {
+    let rule = sudoku.rule;
+    let value_count = rule.note.len();
+
+    for i in 0 .. rule.size {
+      if rule.constraints(i).iter().any(|c| !c.distinct()) {
+        return Err(SudokuError::Unsupported("exact cover solver only handles no-duplicate constraints".to_string()));
+      }
+    }
+
+    let mut primary = 0;
+    let mut cell_column = vec![usize::MAX; rule.size];
+    for (i, column) in cell_column.iter_mut().enumerate() {
+      if sudoku.flags[i] & FLAG_IGNORED == 0 {
+        *column = primary;
+        primary += 1;
+      }
+    }
+
+    // Columns of each (group, value), or (pair, value) for neighbours not
+    // sharing any group.
+    let mut full = vec![];
+    let mut partial = vec![];
+    for group in rule.groups() {
+      if group.len() == value_count {
+        full.push(group.clone());
+      } else {
+        partial.push(group.clone());
+      }
+    }
+    for i in 0 .. rule.size {
+      for constr in rule.constraints(i) {
+        for &j in constr.neighbours() {
+          let pair = vec![i, j];
+          if i < j
+            && !rule.groups().iter().any(|g| g.contains(&i) && g.contains(&j))
+            && !partial.contains(&pair)
+          {
+            partial.push(pair);
+          }
+        }
+      }
+    }
+
+    let full_start = primary;
+    primary += full.len() * value_count;
+    let partial_start = primary;
+    let column_count = primary + partial.len() * value_count;
+
+    let mut dlx = Dlx {
+      sudoku: sudoku.snapshot(),
+      left: vec![],
+      right: vec![],
+      up: vec![],
+      down: vec![],
+      column: vec![],
+      row: vec![],
+      column_size: vec![0; column_count + 1],
+      candidates: vec![],
+      stack: vec![],
+    };
+
+    // Root and headers. Only primary columns are linked to the root.
+    for header in 0 ..= column_count {
+      dlx.up.push(header);
+      dlx.down.push(header);
+      dlx.column.push(header);
+      dlx.row.push(usize::MAX);
+      if header == 0 || header > primary {
+        dlx.left.push(header);
+        dlx.right.push(header);
+      } else {
+        dlx.left.push(header - 1);
+        dlx.right.push(if header == primary { ROOT } else { header + 1 });
+      }
+    }
+    if primary > 0 {
+      dlx.left[ROOT] = primary;
+      dlx.right[ROOT] = 1;
+    }
+
+    let mut columns = vec![];
+    for (i, &column) in cell_column.iter().enumerate() {
+      if column == usize::MAX {
+        continue;
+      }
+      let note = sudoku.board[i];
+      for (value, &bit) in rule.note.iter().enumerate() {
+        if note & bit == rule.zero {
+          continue;
+        }
+        columns.clear();
+        columns.push(column);
+        for (g, group) in full.iter().enumerate() {
+          if group.contains(&i) {
+            columns.push(full_start + g * value_count + value);
+          }
+        }
+        for (g, group) in partial.iter().enumerate() {
+          if group.contains(&i) {
+            columns.push(partial_start + g * value_count + value);
+          }
+        }
+        dlx.add_row(&columns);
+        dlx.candidates.push((i, value));
+      }
+    }
+
+    Ok(dlx)
+  }
+
+  fn add_row(&mut self, columns: &[usize]) {
+    let row = self.candidates.len();
+    let first = self.left.len();
+    for (k, &c) in columns.iter().enumerate() {
+      let header = c + 1;
+      let node = self.left.len();
+      self.left.push(if k == 0 { node } else { node - 1 });
+      self.right.push(first);
+      self.right[self.left[node]] = node;
+      self.left[first] = node;
+      self.up.push(self.up[header]);
+      self.down.push(header);
+      self.down[self.up[header]] = node;
+      self.up[header] = node;
+      self.column.push(header);
+      self.row.push(row);
+      self.column_size[header] += 1;
+    }
+  }
+
+  fn cover(&mut self, header: usize) {
+    self.right[self.left[header]] = self.right[header];
+    self.left[self.right[header]] = self.left[header];
+    let mut i = self.down[header];
+    while i != header {
+      let mut j = self.right[i];
+      while j != i {
+        self.down[self.up[j]] = self.down[j];
+        self.up[self.down[j]] = self.up[j];
+        self.column_size[self.column[j]] -= 1;
+        j = self.right[j];
+      }
+      i = self.down[i];
+    }
+  }
+
+  fn uncover(&mut self, header: usize) {
+    let mut i = self.up[header];
+    while i != header {
+      let mut j = self.left[i];
+      while j != i {
+        self.column_size[self.column[j]] += 1;
+        self.down[self.up[j]] = j;
+        self.up[self.down[j]] = j;
+        j = self.left[j];
+      }
+      i = self.up[i];
+    }
+    self.right[self.left[header]] = header;
+    self.left[self.right[header]] = header;
+  }
+
+  fn choose_column(&self) -> usize {
+    let mut best = ROOT;
+    let mut best_size = usize::MAX;
+    let mut header = self.right[ROOT];
+    while header != ROOT {
+      if self.column_size[header] < best_size {
+        best = header;
+        best_size = self.column_size[header];
+      }
+      header = self.right[header];
+    }
+    best
+  }
+
+  fn search(&mut self, found: &mut dyn FnMut(&Dlx<'a, T>) -> bool) -> bool {
+    let header = self.choose_column();
+    if header == ROOT {
+      return found(self);
+    }
+
+    self.cover(header);
+    let mut i = self.down[header];
+    let mut stop = false;
+    while i != header && !stop {
+      self.stack.push(self.row[i]);
+      let mut j = self.right[i];
+      while j != i {
+        self.cover(self.column[j]);
+        j = self.right[j];
+      }
+      stop = self.search(found);
+      let mut j = self.left[i];
+      while j != i {
+        self.uncover(self.column[j]);
+        j = self.left[j];
+      }
+      self.stack.pop();
+      i = self.down[i];
+    }
+    self.uncover(header);
+    stop
+  }
+
+  fn to_sudoku(&self) -> Sudoku<'a, T> {
+    let rule = self.sudoku.rule;
+    let mut result = self.sudoku.snapshot();
+    for &row in self.stack.iter() {
+      let (index, value) = self.candidates[row];
+      result.board[index] = rule.note[value];
+      result.flags[index] |= FLAG_OK;
+    }
+    result
+  }
+
+  /// Count solutions, stopping once `limit` is reached.
+  pub fn count_solution(&mut self, limit: usize) -> usize {
+    let mut result = 0;
+    if limit == 0 {
+      return result;
+    }
+    self.search(&mut |_| {
+      result += 1;
+      result >= limit
+    });
+    result
+  }
+
+  /// Push solutions into `dst` until it holds `limit` of them.
+  pub fn solve(&mut self, dst: &mut Vec<Sudoku<'a, T>>, limit: usize) {
+    if dst.len() >= limit {
+      return;
+    }
+    self.search(&mut |dlx| {
+      dst.push(dlx.to_sudoku());
+      dst.len() >= limit
+    });
+  }
+
+}
@@ -0,0 +1,91 @@
+use std::{error, fmt::{self, Display, Formatter}, io};
+
+/// Error returned by the fallible parts of the API.
+#[derive(Debug)]
+pub enum SudokuError {
+  /// Reading the input failed.
+  Io(io::Error),
+  /// Text at `line`:`column` (1-based) couldn't be parsed.
+  Parse { line: usize, column: usize, text: String },
+  /// A single line puzzle has `found` cells instead of `expected`.
+  CellCount { expected: usize, found: usize },
+  /// Value at `line`:`column` is larger than the largest value of the rule.
+  ValueOutOfRange { line: usize, column: usize, value: usize, max: usize },
+  /// Setting `value` at `cell` contradicts the other givens. `other` is the
+  /// given it directly conflicts with, `constraint` the name of the violated
+  /// constraint. Cells are 1-based (row, column).
+  Contradiction {
+    cell: (usize, usize),
+    value: usize,
+    other: Option<(usize, usize)>,
+    constraint: Option<String>,
+  },
+  /// The note type doesn't have enough bits for `max_value` values.
+  RuleTooLarge { max_value: u32 },
+  /// Cell `index` is outside a board of `size` cells.
+  InvalidIndex { index: usize, size: usize },
+  /// The alphabet can't be used with the rule.
+  InvalidAlphabet(String),
+  /// The rule uses a feature the chosen solver can't handle.
+  Unsupported(String),
+  /// The region map can't be used with the rule.
+  InvalidRegions(String),
+  /// An imported puzzle is malformed.
+  InvalidImport(String),
+  /// Cells of a line don't follow each other.
+  InvalidLine(String),
+}
+
+impl Display for SudokuError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      SudokuError::Io(err) => write!(f, "{}", err),
+      SudokuError::Parse { line, column, text } => {
+        write!(f, "{}:{}: can't parse \"{}\"", line, column, text)
+      },
+      SudokuError::CellCount { expected, found } => {
+        write!(f, "expected {} cells, found {}", expected, found)
+      },
+      SudokuError::ValueOutOfRange { line, column, value, max } => {
+        write!(f, "{}:{}: value {} is out of range 1..={}", line, column, value, max)
+      },
+      SudokuError::Contradiction { cell, value, other, constraint } => {
+        write!(f, "value {} at r{}c{}", value, cell.0, cell.1)?;
+        match other {
+          Some(other) => write!(f, " conflicts with r{}c{}", other.0, other.1)?,
+          None => write!(f, " contradicts the other givens")?,
+        }
+        match constraint {
+          Some(name) => write!(f, " ({})", name),
+          None => Ok(()),
+        }
+      },
+      SudokuError::RuleTooLarge { max_value } => {
+        write!(f, "note type is too small to hold {} values", max_value)
+      },
+      SudokuError::InvalidIndex { index, size } => {
+        write!(f, "cell index {} is outside the board of {} cells", index, size)
+      },
+      SudokuError::InvalidAlphabet(what) => write!(f, "invalid alphabet: {}", what),
+      SudokuError::Unsupported(what) => write!(f, "unsupported: {}", what),
+      SudokuError::InvalidRegions(what) => write!(f, "invalid regions: {}", what),
+      SudokuError::InvalidImport(what) => write!(f, "invalid import: {}", what),
+      SudokuError::InvalidLine(what) => write!(f, "invalid line: {}", what),
+    }
+  }
+}
+
+impl error::Error for SudokuError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      SudokuError::Io(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for SudokuError {
+  fn from(err: io::Error) -> SudokuError {
+    SudokuError::Io(err)
+  }
+}
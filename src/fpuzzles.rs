@@ -0,0 +1,274 @@
+use serde_json::{Map, Value};
+
+use super::{directive::apply_directive, error::SudokuError, rules::RuleSpec};
+
+// Only the f-puzzles JSON format is read. SudokuPad puzzles have to be
+// exported to f-puzzles first, their own format isn't supported. f-puzzles
+// describes a puzzle as a JSON object such as
+//
+//   {
+//     "size": 9,
+//     "grid": [[{"value": 5, "given": true}, {}, ...], ...],
+//     "killercage": [{"cells": ["R1C1", "R1C2"], "value": "15"}],
+//     "thermometer": [{"lines": [["R1C1", "R1C2", "R1C3"]]}],
+//     "difference": [{"cells": ["R1C1", "R1C2"]}]
+//   }
+//
+// The features are mapped onto the settings and directives of a rule file:
+//
+//   diagonal+ and diagonal-      diagonals, both or none
+//   antiknight, antiking         anti-knight, anti-king
+//   nonconsecutive               non-consecutive
+//   killercage                   killer, with a sum
+//   thermometer                  thermo, one per line
+//   arrow                        arrow, one circle cell and one line
+//   difference, ratio            white and black dots
+//   xv                           x and v
+//   negative                     kropki-negative (ratio and difference), xv-negative
+//   sandwichsum                  sandwich, on R0Cy or RxC0
+//   whispers, renban, palindrome german or dutch, renban, palindrome
+//
+// The `region` of grid cells replaces the boxes. Any other feature is
+// rejected.
+
+/// Whether puzzle text is JSON to import rather than a grid.
+pub fn is_json(text: &str) -> bool {
+  text.trim_start().starts_with('{')
+}
+
+fn invalid(what: String) -> SudokuError {
+  SudokuError::InvalidImport(what)
+}
+
+// Whether a feature is absent, f-puzzles keeping empty lists and false flags.
+fn is_empty(value: &Value) -> bool {
+  match value {
+    Value::Null | Value::Bool(false) => true,
+    Value::Array(list) => list.is_empty(),
+    _ => false,
+  }
+}
+
+// Number written either as a number or as a string.
+fn number(value: &Value) -> Option<usize> {
+  match value {
+    Value::Number(n) => n.as_u64().map(|n| n as usize),
+    Value::String(s) => s.trim().parse::<usize>().ok(),
+    _ => None,
+  }
+}
+
+fn array<'a>(value: &'a Value, what: &str) -> Result<&'a Vec<Value>, SudokuError> {
+  value.as_array().ok_or_else(|| invalid(format!("{} isn't a list", what)))
+}
+
+// Row and column of a cell written as `R1C1`, 0 standing for the outside of
+// the grid.
+fn position(value: &Value, size: usize) -> Result<(usize, usize), SudokuError> {
+  let bad = || invalid(format!("bad cell {}", value));
+  let text = value.as_str().ok_or_else(bad)?.to_ascii_lowercase();
+  let (row, column) = text.strip_prefix('r').and_then(|rest| rest.split_once('c')).ok_or_else(bad)?;
+  let row = row.parse::<usize>().ok().filter(|&r| r <= size).ok_or_else(bad)?;
+  let column = column.parse::<usize>().ok().filter(|&c| c <= size).ok_or_else(bad)?;
+  Ok((row, column))
+}
+
+// Cells of a list, written as directive cells.
+fn cells(value: &Value, size: usize, what: &str) -> Result<Vec<String>, SudokuError> {
+  array(value, what)?.iter().map(|cell| {
+    match position(cell, size)? {
+      (row, column) if row > 0 && column > 0 => Ok(format!("r{}c{}", row, column)),
+      _ => Err(invalid(format!("bad cell {}", cell))),
+    }
+  }).collect()
+}
+
+fn field<'a>(entry: &'a Value, key: &str, what: &str) -> Result<&'a Value, SudokuError> {
+  entry.get(key).ok_or_else(|| invalid(format!("{} without {}", what, key)))
+}
+
+// Boxes f-puzzles draws for a grid of `size` cells, wider than tall.
+fn box_size(size: usize) -> (usize, usize) {
+  let height = (1 ..= size).filter(|&h| size.is_multiple_of(h) && h * h <= size).max().unwrap_or(1);
+  (size / height, height)
+}
+
+// Name an error after the feature entry it comes from, such as
+// `killercage[2]`.
+fn in_entry(label: &str, err: SudokuError) -> SudokuError {
+  match err {
+    SudokuError::Unsupported(what) => SudokuError::Unsupported(format!("{}: {}", label, what)),
+    SudokuError::InvalidImport(what) => invalid(format!("{}: {}", label, what)),
+    SudokuError::Parse { text, .. } => invalid(format!("{}: can't parse \"{}\"", label, text)),
+    err => invalid(format!("{}: {}", label, err)),
+  }
+}
+
+// Add the directives of one f-puzzles feature, each with the entry it comes
+// from.
+fn add_feature(directives: &mut Vec<(String, String)>, key: &str, value: &Value, size: usize) -> Result<(), SudokuError> {
+  for (index, entry) in array(value, key)?.iter().enumerate() {
+    let label = format!("{}[{}]", key, index);
+    let mut list = vec![];
+    add_entry(&mut list, key, entry, size).map_err(|err| in_entry(&label, err))?;
+    directives.extend(list.into_iter().map(|directive| (label.clone(), directive)));
+  }
+  Ok(())
+}
+
+// Add the directives of one entry of an f-puzzles feature.
+fn add_entry(directives: &mut Vec<String>, key: &str, entry: &Value, size: usize) -> Result<(), SudokuError> {
+  match key {
+    "killercage" => {
+      let sum = entry.get("value").and_then(number)
+        .ok_or_else(|| SudokuError::Unsupported("killer cage without a sum".to_string()))?;
+      let cells = cells(field(entry, "cells", key)?, size, key)?;
+      directives.push(format!("killer {} {}", sum, cells.join(" ")));
+    },
+    "thermometer" | "whispers" | "renban" | "palindrome" => {
+      let name = match (key, entry.get("value").and_then(number)) {
+        ("thermometer", _) => "thermo",
+        ("whispers", None | Some(5)) => "german",
+        ("whispers", Some(4)) => "dutch",
+        ("whispers", Some(diff)) => return Err(SudokuError::Unsupported(format!("whispers differing by {}", diff))),
+        _ => key,
+      };
+      for line in array(field(entry, "lines", key)?, key)?.iter() {
+        directives.push(format!("{} {}", name, cells(line, size, key)?.join(" ")));
+      }
+    },
+    "arrow" => {
+      let circle = cells(field(entry, "cells", key)?, size, key)?;
+      let lines = array(field(entry, "lines", key)?, key)?;
+      if circle.len() != 1 || lines.len() != 1 {
+        return Err(SudokuError::Unsupported("arrow with several circle cells or lines".to_string()));
+      }
+      let line = cells(&lines[0], size, key)?;
+      let shaft: Vec<String> = line.into_iter().filter(|cell| *cell != circle[0]).collect();
+      directives.push(format!("arrow {} {}", circle[0], shaft.join(" ")));
+    },
+    "difference" | "ratio" | "xv" => {
+      let pair = cells(field(entry, "cells", key)?, size, key)?;
+      if pair.len() != 2 {
+        return Err(invalid(format!("{} on {} cells", key, pair.len())));
+      }
+      let value = entry.get("value");
+      let name = match key {
+        "difference" if value.and_then(number).unwrap_or(1) == 1 => "white",
+        "ratio" if value.and_then(number).unwrap_or(2) == 2 => "black",
+        "xv" => match value.and_then(Value::as_str).map(str::to_ascii_lowercase).as_deref() {
+          Some("x") => "x",
+          Some("v") => "v",
+          _ => return Err(invalid(format!("xv of {}", entry))),
+        },
+        _ => return Err(SudokuError::Unsupported(format!("{} of {}", key, value.unwrap_or(&Value::Null)))),
+      };
+      directives.push(format!("{} {} {}", name, pair[0], pair[1]));
+    },
+    "sandwichsum" => {
+      let cell = field(entry, "cell", key)?;
+      let sum = field(entry, "value", key).map(number)?.ok_or_else(|| invalid(format!("sandwich of {}", entry)))?;
+      let line = match position(cell, size)? {
+        (0, column) if column > 0 => format!("c{}", column),
+        (row, 0) if row > 0 => format!("r{}", row),
+        _ => return Err(invalid(format!("sandwich at {}", cell))),
+      };
+      directives.push(format!("sandwich {} {}", line, sum));
+    },
+    _ => return Err(SudokuError::Unsupported(format!("f-puzzles feature {}", key))),
+  }
+  Ok(())
+}
+
+// Read the givens and regions of the grid, returning the grid as puzzle text.
+fn read_grid(spec: &mut RuleSpec, grid: &Value, size: usize) -> Result<String, SudokuError> {
+  let rows = array(grid, "grid")?;
+  if rows.len() != size {
+    return Err(invalid(format!("grid has {} rows instead of {}", rows.len(), size)));
+  }
+  let mut text = String::new();
+  let mut regions = String::new();
+  let mut has_regions = false;
+  for (y, row) in rows.iter().enumerate() {
+    let row = array(row, "grid row")?;
+    if row.len() != size {
+      return Err(invalid(format!("grid row {} has {} cells instead of {}", y + 1, row.len(), size)));
+    }
+    for (x, cell) in row.iter().enumerate() {
+      let given = cell.get("given").and_then(Value::as_bool).unwrap_or(false);
+      match cell.get("value").and_then(number) {
+        Some(value) if given && value >= 1 && value <= size => text += &value.to_string(),
+        Some(value) if given => return Err(invalid(format!("value {} at r{}c{}", value, y + 1, x + 1))),
+        _ => text.push('.'),
+      }
+      let region = match cell.get("region").and_then(number) {
+        Some(region) => {
+          has_regions = true;
+          region
+        },
+        None => y / spec.height * (size / spec.width) + x / spec.width,
+      };
+      regions += &region.to_string();
+      let separator = if x + 1 < size { " " } else { "\n" };
+      text += separator;
+      regions += separator;
+    }
+  }
+  if has_regions {
+    spec.regions = Some(regions);
+  } else if spec.height == 1 && size > 1 {
+    return Err(SudokuError::Unsupported(format!("size {} without regions", size)));
+  }
+  Ok(text)
+}
+
+/// Import an f-puzzles JSON puzzle, returning the rule and the grid as puzzle
+/// text. Unsupported features are reported as [`SudokuError::Unsupported`].
+pub fn read_fpuzzles(text: &str) -> Result<(RuleSpec, String), SudokuError> {
+  let puzzle: Value = serde_json::from_str(text).map_err(|err| invalid(err.to_string()))?;
+  let puzzle: &Map<String, Value> = puzzle.as_object().ok_or_else(|| invalid("not an object".to_string()))?;
+  let size = puzzle.get("size").and_then(number).filter(|&size| size > 0)
+    .ok_or_else(|| invalid("missing size".to_string()))?;
+
+  let mut spec = RuleSpec::default();
+  (spec.width, spec.height) = box_size(size);
+  let mut directives = vec![];
+  for (key, value) in puzzle.iter().filter(|(_, value)| !is_empty(value)) {
+    match key.as_str() {
+      "size" | "grid" | "title" | "author" | "ruleset" | "solution" | "diagonal+" | "diagonal-" => {},
+      "antiknight" => spec.anti_knight = true,
+      "antiking" => spec.anti_king = true,
+      "nonconsecutive" => spec.non_consecutive = true,
+      "negative" => {
+        let names: Vec<&str> = array(value, key)?.iter().filter_map(Value::as_str).collect();
+        for &name in names.iter() {
+          match name {
+            "ratio" | "difference" if names.contains(&"ratio") && names.contains(&"difference") => spec.kropki_negative = true,
+            "xv" => spec.xv_negative = true,
+            _ => return Err(SudokuError::Unsupported(format!("negative constraint {}", name))),
+          }
+        }
+      },
+      "killercage" | "thermometer" | "arrow" | "difference" | "ratio" | "xv" | "sandwichsum"
+      | "whispers" | "renban" | "palindrome" => add_feature(&mut directives, key, value, size)?,
+      _ => return Err(SudokuError::Unsupported(format!("f-puzzles feature {}", key))),
+    }
+  }
+  match (puzzle.get("diagonal+").is_some_and(|v| !is_empty(v)), puzzle.get("diagonal-").is_some_and(|v| !is_empty(v))) {
+    (true, true) => spec.diagonals = true,
+    (false, false) => {},
+    _ => return Err(SudokuError::Unsupported("a single diagonal".to_string())),
+  }
+
+  let grid = puzzle.get("grid").ok_or_else(|| invalid("missing grid".to_string()))?;
+  let grid = read_grid(&mut spec, grid, size)?;
+
+  // Check every directive now, naming its entry, as they have no line in the
+  // puzzle to report.
+  let mut rule = spec.build(1u128)?;
+  for (label, directive) in directives.into_iter() {
+    apply_directive(&mut rule, 0, &directive).map_err(|err| in_entry(&label, err))?;
+    spec.directives.push((0, directive));
+  }
+  Ok((spec, grid))
+}
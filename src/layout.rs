@@ -0,0 +1,151 @@
+use super::{
+  constraint::{ConstraintList, ConstraintListGenerator, GGBlock, GGHorizontal, GGVertical, GroupGenerator},
+  error::SudokuError,
+  sudoku::{Note, Rule},
+};
+
+/// Several standard grids with boxes of `block_width` x `block_height` cells
+/// placed on one board, overlapping or not, such as a Samurai. Cells outside
+/// of every grid are ignored.
+#[derive(Clone, Debug)]
+pub struct Layout {
+  /// Board width in cells.
+  pub width: usize,
+  /// Board height in cells.
+  pub height: usize,
+  block_width: usize,
+  block_height: usize,
+  grids: Vec<(usize, usize)>,
+}
+
+impl Layout {
+  /// Empty layout for grids with boxes of `block_width` x `block_height`
+  /// cells.
+  pub fn new(block_width: usize, block_height: usize) -> Layout {
+    Layout {
+      width: 0,
+      height: 0,
+      block_width,
+      block_height,
+      grids: vec![],
+    }
+  }
+
+  /// Number of values, and cells on the side of a grid.
+  pub fn grid_size(&self) -> usize {
+    self.block_width * self.block_height
+  }
+
+  /// Place a grid with its top left cell at (`dx`, `dy`).
+  pub fn add_grid(&mut self, dx: usize, dy: usize) -> &mut Layout {
+    let size = self.grid_size();
+    self.width = self.width.max(dx + size);
+    self.height = self.height.max(dy + size);
+    self.grids.push((dx, dy));
+    self
+  }
+
+  /// Top left cell of each grid.
+  pub fn grids(&self) -> &[(usize, usize)] {
+    &self.grids
+  }
+
+  /// A single standard grid.
+  pub fn single(block_width: usize, block_height: usize) -> Layout {
+    let mut layout = Layout::new(block_width, block_height);
+    layout.add_grid(0, 0);
+    layout
+  }
+
+  /// Two grids sharing the box at their corner.
+  pub fn twodoku(block_width: usize, block_height: usize) -> Layout {
+    let mut layout = Layout::new(block_width, block_height);
+    let size = layout.grid_size();
+    layout.add_grid(0, 0);
+    layout.add_grid(size - block_width, size - block_height);
+    layout
+  }
+
+  /// Four grids, each one box away from its neighbours, on a board one box
+  /// larger than a grid.
+  pub fn butterfly(block_width: usize, block_height: usize) -> Layout {
+    let mut layout = Layout::new(block_width, block_height);
+    for dy in [0, block_height] {
+      for dx in [0, block_width] {
+        layout.add_grid(dx, dy);
+      }
+    }
+    layout
+  }
+
+  /// Four grids at the corners sharing one box each with a grid at the
+  /// center.
+  pub fn samurai(block_width: usize, block_height: usize) -> Layout {
+    let mut layout = Layout::new(block_width, block_height);
+    let size = layout.grid_size();
+    let (x, y) = (size - block_width, size - block_height);
+    layout.add_grid(0, 0);
+    layout.add_grid(x * 2, 0);
+    layout.add_grid(x, y);
+    layout.add_grid(0, y * 2);
+    layout.add_grid(x * 2, y * 2);
+    layout
+  }
+
+  /// Layout by name: `single`, `twodoku`, `butterfly` or `samurai`.
+  pub fn named(name: &str, block_width: usize, block_height: usize) -> Option<Layout> {
+    match name {
+      "single" => Some(Layout::single(block_width, block_height)),
+      "twodoku" => Some(Layout::twodoku(block_width, block_height)),
+      "butterfly" => Some(Layout::butterfly(block_width, block_height)),
+      "samurai" => Some(Layout::samurai(block_width, block_height)),
+      _ => None,
+    }
+  }
+
+  /// Whether the cell at (`x`, `y`) belongs to a grid.
+  pub fn contains(&self, x: usize, y: usize) -> bool {
+    let size = self.grid_size();
+    self.grids.iter().any(|&(dx, dy)| dx <= x && x < dx + size && dy <= y && y < dy + size)
+  }
+
+  /// Columns, rows and boxes of every grid.
+  pub fn group_generators<T, N>(&self, nd: &ConstraintListGenerator<T, N>) -> Vec<Box<dyn GroupGenerator>>
+  where T: ConstraintList<N>, N: Note
+  {
+    let (bw, bh) = (self.block_width, self.block_height);
+    let size = self.grid_size();
+    let mut result: Vec<Box<dyn GroupGenerator>> = vec![];
+    for &(dx, dy) in self.grids.iter() {
+      result.push(Box::new(GGVertical::new(nd, size, size, dx, dy)));
+      result.push(Box::new(GGHorizontal::new(nd, size, size, dx, dy)));
+      result.push(Box::new(GGBlock::new(nd, bh, bw, bw, bh, dx, dy)));
+    }
+    result
+  }
+
+  /// Add columns, rows and boxes of every grid to `nd`.
+  pub fn add_groups<T, N>(&self, nd: &mut ConstraintListGenerator<T, N>) -> Result<(), SudokuError>
+  where T: ConstraintList<N>, N: Note
+  {
+    for gen in self.group_generators(nd) {
+      nd.add_group_generator(gen.as_ref())?;
+    }
+    Ok(())
+  }
+
+  /// Empty rule for the board, with the cells outside of every grid ignored.
+  pub fn rule<T: Note>(&self, one: T) -> Result<Rule<T>, SudokuError> {
+    let size = self.grid_size();
+    let mut rule = Rule::new(self.width, self.height, size as u32, one)?;
+    rule.set_grid(self.block_width, self.block_height);
+    for y in 0 .. self.height {
+      for x in 0 .. self.width {
+        if !self.contains(x, y) {
+          rule.ignore_cell(y * self.width + x)?;
+        }
+      }
+    }
+    Ok(rule)
+  }
+}
@@ -0,0 +1,58 @@
+//! A general sudoku solver and generator.
+//!
+//! A [`Rule`] describes the board and the constraints attached to each cell,
+//! usually built with a [`ConstraintListGenerator`]. A [`Sudoku`] holds the
+//! state of a board under a rule and can be solved, counted or generated.
+//! Rules own their constraints, so they can be cloned, returned from
+//! functions and shared between threads.
+//!
+//! ```
+//! use sudoku_rs::{ConstraintListGenerator, NoDuplicate, Rule, Sudoku};
+//!
+//! let mut rule = Rule::<u16>::new(9, 9, 9, 1).unwrap();
+//! rule.set_grid(3, 3);
+//!
+//! let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+//! nodup.add_standard_group(3, 3, 3, 3, 0, 0).unwrap();
+//! nodup.apply(&mut rule).unwrap();
+//!
+//! let puzzle = Sudoku::generate(&rule, 100.0).unwrap();
+//! let mut copy = puzzle.clone();
+//! assert_eq!(copy.count_solution(2), 1);
+//! ```
+
+pub mod alphabet;
+pub mod constraint;
+pub mod directive;
+pub mod dlx;
+pub mod error;
+pub mod fpuzzles;
+pub mod layout;
+pub mod rules;
+pub mod selector;
+pub mod sudoku;
+mod propagate;
+
+pub use alphabet::Alphabet;
+pub use constraint::{
+  Arrow,
+  ConstraintList,
+  ConstraintListGenerator,
+  GroupGenerator,
+  KillerCage,
+  Kropki,
+  Line,
+  LineKind,
+  NoConsecutive,
+  NoDuplicate,
+  Pairwise,
+  Sandwich,
+  Thermometer,
+  Xv,
+};
+pub use dlx::Dlx;
+pub use error::SudokuError;
+pub use layout::Layout;
+pub use rules::RuleSpec;
+pub use selector::{CellSelector, FirstUnsolved, MinRemaining, MinRemainingDegree};
+pub use sudoku::{Collapsed, Constraint, Note, Rule, Sudoku};
@@ -1,25 +1,22 @@
-#![allow(dead_code)]
+use std::{env::{self, Args}, error, fs::File, io::{self, Read}, process::ExitCode};
+use sudoku_rs::{fpuzzles::{is_json, read_fpuzzles}, ConstraintListGenerator, Dlx, Layout, NoDuplicate, Note, Rule, RuleSpec, Sudoku, SudokuError};
 
-use std::{env::{self, Args}, error, io, usize};
-use sudoku::{Note, Rule, Sudoku};
-use constraint::{ConstraintListGenerator, NoDuplicate};
-use rand::{thread_rng, seq::SliceRandom};
-
-mod sudoku;
-mod constraint;
-
-fn solve<T: Note>(rule: &Rule<T>) {
-  let mut s = Sudoku::new(rule);
-
-  let mut stdin = io::stdin();
-  if let Err(e) = s.read_from(&mut stdin) {
-    println!("{}", e);
-    return;
+fn find_solutions<'a, T: Note>(s: &mut Sudoku<'a, T>, use_dlx: bool) -> Result<Vec<Sudoku<'a, T>>, SudokuError> {
+  let mut solutions = vec![];
+  if use_dlx {
+    Dlx::new(s)?.solve(&mut solutions, 2);
+  } else {
+    s.solve(&mut solutions, 2);
   }
+  Ok(solutions)
+}
 
-  let mut solutions = vec![];
-  s.solve(&mut solutions, 2);
+// Exit with FAILURE when the puzzle has no solution.
+fn solve<T: Note>(rule: &Rule<T>, text: &str, use_dlx: bool) -> Result<ExitCode, SudokuError> {
+  let mut s = Sudoku::new(rule);
+  s.read_str(text)?;
 
+  let solutions = find_solutions(&mut s, use_dlx)?;
   println!("{} Solution found", solutions.len());
   for (i, solution) in solutions.iter().enumerate() {
     if i > 0 {
@@ -27,89 +24,347 @@ fn solve<T: Note>(rule: &Rule<T>) {
     }
     println!("{}", solution);
   }
+  Ok(if solutions.is_empty() { ExitCode::FAILURE } else { ExitCode::SUCCESS })
 }
 
-fn generate<T: Note>(rule: &Rule<T>, remove_amount: f32) {
+fn validate<T: Note>(rule: &Rule<T>, text: &str) -> Result<(), SudokuError> {
   let mut s = Sudoku::new(rule);
+  s.read_str(text)?;
+  println!("Valid");
+  Ok(())
+}
 
-  {
-    let mut solutions = vec![];
-    s.solve_random(&mut solutions, 1);
-    if let Some(sol) = solutions.pop() {
-      s = sol;
-      s.make_fixed();
-    } else {
-      println!("No solution found");
-      return;
+// Solve or validate every line of the input, printing one result per line.
+// Exit with the code of the last invalid puzzle if any, or with FAILURE when a
+// puzzle has no solution.
+fn batch<T: Note>(rule: &Rule<T>, input: &mut dyn Read, cmd: &str) -> Result<ExitCode, SudokuError> {
+  let mut buffer = String::new();
+  input.read_to_string(&mut buffer)?;
+
+  let mut invalid = None;
+  let mut unsolved = false;
+  for line in buffer.lines() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let mut s = Sudoku::new(rule);
+    let solutions = match s.read_line(line) {
+      Ok(()) if cmd == "validate" => {
+        println!("valid");
+        continue;
+      },
+      Ok(()) => find_solutions(&mut s, cmd == "dlx")?,
+      Err(err) => {
+        println!("invalid {}", err);
+        invalid = Some(err);
+        continue;
+      },
+    };
+    match solutions.first() {
+      Some(solution) => println!("{} {}", solutions.len(), solution.to_line()),
+      None => {
+        println!("0");
+        unsolved = true;
+      },
     }
   }
+  Ok(match invalid {
+    Some(err) => exit_code(&err),
+    None if unsolved => ExitCode::FAILURE,
+    None => ExitCode::SUCCESS,
+  })
+}
 
-  let mut list: Vec<usize> = (0 .. rule.size).collect();
-  list.shuffle(&mut thread_rng());
+// Print the puzzle after the header describing its rule, or as a single line.
+fn generate<T: Note>(rule: &Rule<T>, header: &str, remove_amount: f32, lines: bool) -> ExitCode {
+  match Sudoku::generate(rule, remove_amount) {
+    Some(s) if lines => {
+      println!("{}", s.to_line());
+      ExitCode::SUCCESS
+    },
+    Some(s) => {
+      print!("{}", header);
+      println!("{}", s);
+      ExitCode::SUCCESS
+    },
+    None => {
+      eprintln!("No solution found");
+      ExitCode::FAILURE
+    },
+  }
+}
+
+fn exit_code(err: &SudokuError) -> ExitCode {
+  ExitCode::from(match err {
+    SudokuError::Io(_) => 3,
+    SudokuError::Parse { .. }
+    | SudokuError::CellCount { .. }
+    | SudokuError::ValueOutOfRange { .. }
+    | SudokuError::InvalidImport(_) => 4,
+    SudokuError::Contradiction { .. } => 5,
+    SudokuError::RuleTooLarge { .. }
+    | SudokuError::InvalidIndex { .. }
+    | SudokuError::InvalidAlphabet(_)
+    | SudokuError::Unsupported(_)
+    | SudokuError::InvalidRegions(_)
+    | SudokuError::InvalidLine(_) => 6,
+  })
+}
+
+struct Options {
+  cmd: String,
+  spec: RuleSpec,
+  remove_amount: f32,
+  input: Option<String>,
+  regions: Option<String>,
+  rules: Option<String>,
+  // First option describing the rule, which an imported puzzle can't take.
+  rule_option: Option<String>,
+  puzzle: Option<String>,
+  lines: bool,
+}
 
-  let end = (rule.size as f32 * remove_amount / 100.0).floor().abs() as usize;
-  for _ in 0 .. end {
-    let index = match list.pop() {
-      Some(v) => v,
-      None => break,
+// Read the region map and the rule file of the options into the spec, the
+// rule file applying on top of the other options, then the puzzle to solve
+// with its header and directives. Imported puzzles carry their own rule and
+// can't be combined with rule options.
+fn read_files(opts: &mut Options) -> Result<(), SudokuError> {
+  if let Some(filename) = &opts.regions {
+    let mut text = String::new();
+    File::open(filename)?.read_to_string(&mut text)?;
+    opts.spec.regions = Some(text);
+  }
+  if let Some(filename) = &opts.rules {
+    let mut text = String::new();
+    File::open(filename)?.read_to_string(&mut text)?;
+    opts.spec.read_str(&text)?;
+  }
+  if ["solve", "dlx", "validate", "import"].contains(&opts.cmd.as_str()) && !opts.lines {
+    let mut input: Box<dyn Read> = match &opts.input {
+      Some(filename) => Box::new(File::open(filename)?),
+      None => Box::new(io::stdin()),
     };
-    let mut copy = s.clone();
-    copy.unfixed(index);
-    let solution_count = copy.count_solution(2);
-    if solution_count == 1 {
-      s.unfixed(index);
-    } 
-  }
-  println!("{}", s);
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    if opts.cmd == "import" || is_json(&text) {
+      if let Some(option) = &opts.rule_option {
+        return Err(SudokuError::Unsupported(format!("{} with an f-puzzles puzzle", option)));
+      }
+      let (spec, grid) = read_fpuzzles(&text)?;
+      opts.spec = spec;
+      opts.puzzle = Some(grid);
+    } else {
+      opts.spec.read_header(&text)?;
+      opts.spec.read_directives(&text);
+      opts.puzzle = Some(text);
+    }
+  }
+  Ok(())
 }
 
-fn get_args(args: &mut Args) -> Result<(usize, usize, f32), Box<dyn error::Error>> {
-  Ok((match args.next() {
-    Some(v) => v.parse::<usize>()?,
-    None => 3,
-  }, match args.next() {
-    Some(v) => v.parse::<usize>()?,
-    None => 3,
-  }, match args.next() {
-    Some(v) => v.parse::<f32>()?,
-    None => 100.0,
-  }))
+// Print every group generator of the rule.
+fn show_groups<T: Note>(spec: &RuleSpec, one: T) -> Result<(), SudokuError> {
+  let layout = spec.layout()?;
+  let rule = layout.rule(one)?;
+  let nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  let regions = spec.regions(&nodup)?;
+  for (i, gen) in spec.group_generators(&layout, &nodup, regions)?.iter().enumerate() {
+    if i > 0 {
+      println!();
+    }
+    print!("{}", gen.as_ref());
+  }
+  Ok(())
 }
 
-fn main() {
+fn run<T: Note>(opts: &Options, one: T) -> Result<ExitCode, SudokuError> {
+  if opts.cmd == "groups" {
+    show_groups(&opts.spec, one)?;
+    return Ok(ExitCode::SUCCESS);
+  }
+  let rule = opts.spec.build(one)?;
+
+  if opts.cmd == "gen" {
+    return Ok(generate(&rule, &opts.spec.to_header(), opts.remove_amount, opts.lines));
+  }
+  if let Some(text) = &opts.puzzle {
+    if opts.cmd == "import" {
+      Sudoku::new(&rule).read_str(text)?;
+      print!("{}{}", opts.spec.to_header(), text);
+    } else if opts.cmd == "validate" {
+      validate(&rule, text)?;
+    } else {
+      return solve(&rule, text, opts.cmd == "dlx");
+    }
+    return Ok(ExitCode::SUCCESS);
+  }
+
+  let mut input: Box<dyn Read> = match &opts.input {
+    Some(filename) => Box::new(File::open(filename)?),
+    None => Box::new(io::stdin()),
+  };
+  batch(&rule, &mut input, &opts.cmd)
+}
+
+// Pick the smallest note type holding every value.
+fn run_sized(opts: &Options) -> Result<ExitCode, SudokuError> {
+  match opts.spec.value_count() {
+    0 ..= 16 => run::<u16>(opts, 1),
+    17 ..= 32 => run::<u32>(opts, 1),
+    33 ..= 64 => run::<u64>(opts, 1),
+    _ => run::<u128>(opts, 1),
+  }
+}
+
+// Options describing the rule rather than the input.
+const RULE_OPTIONS: [&str; 10] = [
+  "--alphabet",
+  "--layout",
+  "--regions",
+  "--rules",
+  "--diagonals",
+  "--windows",
+  "--anti-knight",
+  "--anti-king",
+  "--diagonal-neighbour",
+  "--non-consecutive",
+];
+
+fn get_args(cmd: String, args: Args) -> Result<Options, Box<dyn error::Error>> {
+  let mut spec = RuleSpec::default();
+  let mut input = None;
+  let mut regions = None;
+  let mut rules = None;
+  let mut lines = false;
+  let mut positional = vec![];
+  let mut rule_option = None;
+  let mut args = args;
+  while let Some(arg) = args.next() {
+    if RULE_OPTIONS.contains(&arg.as_str()) && rule_option.is_none() {
+      rule_option = Some(arg.clone());
+    }
+    match arg.as_str() {
+      "--alphabet" => spec.alphabet = Some(args.next().ok_or("missing value for --alphabet")?),
+      "--input" => input = Some(args.next().ok_or("missing value for --input")?),
+      "--layout" => spec.layout = args.next().ok_or("missing value for --layout")?,
+      "--regions" => regions = Some(args.next().ok_or("missing value for --regions")?),
+      "--rules" => rules = Some(args.next().ok_or("missing value for --rules")?),
+      "--diagonals" => spec.diagonals = true,
+      "--windows" => spec.windows = true,
+      "--anti-knight" => spec.anti_knight = true,
+      "--anti-king" => spec.anti_king = true,
+      "--diagonal-neighbour" => spec.diagonal_neighbour = true,
+      "--non-consecutive" => spec.non_consecutive = true,
+      "--lines" => lines = true,
+      _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg).into()),
+      _ => positional.push(arg),
+    }
+  }
+
+  if Layout::named(&spec.layout, 1, 1).is_none() {
+    return Err(format!("unknown layout {}", spec.layout).into());
+  }
+  if regions.is_some() && spec.layout != "single" {
+    return Err("--regions only works with a single grid".into());
+  }
+  if cmd != "gen" && !positional.is_empty() && rule_option.is_none() {
+    rule_option = Some("width and height".to_string());
+  }
+  if cmd == "import" {
+    if lines {
+      return Err("--lines doesn't work with import".into());
+    }
+    if let Some(option) = &rule_option {
+      return Err(format!("{} doesn't work with import", option).into());
+    }
+  }
+
+  let mut positional = positional.into_iter();
+  if let Some(v) = positional.next() {
+    spec.width = v.parse::<usize>()?;
+  }
+  if let Some(v) = positional.next() {
+    spec.height = v.parse::<usize>()?;
+  }
+  Ok(Options {
+    cmd,
+    spec,
+    remove_amount: match positional.next() {
+      Some(v) => v.parse::<f32>()?,
+      None => 100.0,
+    },
+    input,
+    regions,
+    rules,
+    rule_option,
+    puzzle: None,
+    lines,
+  })
+}
+
+fn main() -> ExitCode {
   let mut args = env::args();
   let name = args.next().unwrap();
 
   if let Some(cmd) = args.next() {
-    let (width, height, remove_amount) = match get_args(&mut args) {
-      Ok(v) => v,
-      Err(err) => {
-        println!("{}", err);
-        return;
-      }
-    };
-    let size = width * height;
-    let mut rule = Rule::<u16>::new(size, size, size.try_into().unwrap(), 1);
-    rule.set_grid(width, height);
-
-    let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
-    nodup.add_standard_group(height, width, width, height, 0, 0);
-    nodup.apply(&mut rule);
-
-    if cmd == "solve" {
-      solve(&rule);
-      return;
-    } else if cmd == "gen" {
-      generate(&rule, remove_amount);
-      return;
+    if ["solve", "dlx", "validate", "gen", "groups", "import"].contains(&cmd.as_str()) {
+      let mut opts = match get_args(cmd, args) {
+        Ok(v) => v,
+        Err(err) => {
+          eprintln!("{}", err);
+          return ExitCode::from(2);
+        }
+      };
+      return match read_files(&mut opts).and_then(|_| run_sized(&opts)) {
+        Ok(code) => code,
+        Err(err) => {
+          eprintln!("{}", err);
+          exit_code(&err)
+        }
+      };
     }
   }
 
   println!("Usage:");
-  println!("  {} solve [width] [height]", name);
-  println!("  {} gen [width] [height] [remove amount]", name);
+  println!("  {} solve [options] [width] [height]", name);
+  println!("  {} dlx [options] [width] [height]", name);
+  println!("  {} validate [options] [width] [height]", name);
+  println!("  {} gen [options] [width] [height] [remove amount]", name);
+  println!("  {} groups [options] [width] [height]", name);
+  println!("  {} import [options]", name);
   println!("Where:");
   println!("  width        : Block height of the sudoku (default=3)");
   println!("  height       : Block width of the sudoku (default=3)");
   println!("  remove amount: Try to remove this much from the sudoku (default=100)");
+  println!("Options:");
+  println!("  --alphabet NAME: Symbols used for values, one of decimal (default),");
+  println!("                   digits (1-9), hex (0-F), letters (A-Z), alnum (1-9A-Z)");
+  println!("                   or the list of symbols itself");
+  println!("  --input FILE   : Read the puzzle from FILE instead of stdin. Header lines");
+  println!("                   of the puzzle, starting with %, are read as a rule file");
+  println!("  --layout NAME  : Place several grids on the board, one of single (default),");
+  println!("                   twodoku, butterfly or samurai");
+  println!("  --regions FILE : Use the irregular regions of FILE instead of boxes,");
+  println!("                   one region id per cell, such as AAABBBCCC lines");
+  println!("  --rules FILE   : Read the variant from a rule file, applied on top of");
+  println!("                   the other options");
+  println!("  --diagonals    : Values don't repeat on the two diagonals (X-sudoku)");
+  println!("  --windows      : Values don't repeat in the extra windows of hyper sudoku");
+  println!("  --anti-knight  : Cells a knight move apart hold different values");
+  println!("  --anti-king    : Cells a king move apart hold different values");
+  println!("  --diagonal-neighbour: Diagonally adjacent cells hold different values");
+  println!("  --non-consecutive: Orthogonally adjacent cells aren't consecutive");
+  println!("  --lines        : One puzzle per line, with one symbol per cell and");
+  println!("                   . or 0 for empty cells. Print one result per line:");
+  println!("                   the number of solutions (up to 2) and the first one");
+  println!("The dlx command solve using the exact cover solver instead.");
+  println!("The validate command check the givens without solving.");
+  println!("The groups command print the groups of the board.");
+  println!("The import command convert an f-puzzles JSON puzzle into a puzzle file;");
+  println!("solve, dlx and validate also read such JSON puzzles directly.");
+  println!("Exit code:");
+  println!("  1: no solution found, 2: invalid argument, 3: can't read input,");
+  println!("  4: invalid puzzle, 5: contradictory givens, 6: unsupported rule");
+  ExitCode::from(2)
 }
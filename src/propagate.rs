@@ -0,0 +1,182 @@
+use super::sudoku::{Collapsed, FLAG_NONE, FLAG_OK, Note, Sudoku};
+
+// Largest naked subset (pair, triple, ...) searched for in a group.
+const MAX_SUBSET: usize = 3;
+
+fn merge(result: &mut Collapsed, other: Collapsed) -> bool {
+  match other {
+    Collapsed::Ok => *result = Collapsed::Ok,
+    Collapsed::Error => return false,
+    Collapsed::Unchanged => {},
+  }
+  true
+}
+
+impl<T: Note> Sudoku<'_, T> {
+
+  /// Apply hidden singles, naked subsets and pointing/claiming on every group
+  /// registered in the rule. Stop after the first kind of deduction that
+  /// changed something so the cheaper naked singles get a chance first.
+  pub fn propagate_groups(&mut self) -> Collapsed {
+    let rule = self.rule;
+    let mut result = Collapsed::Unchanged;
+
+    for group in rule.groups() {
+      if !merge(&mut result, self.hidden_single(group)) {
+        return Collapsed::Error;
+      }
+    }
+    if let Collapsed::Ok = result {
+      return result;
+    }
+
+    for group in rule.groups() {
+      if !merge(&mut result, self.naked_subset(group)) {
+        return Collapsed::Error;
+      }
+    }
+    if let Collapsed::Ok = result {
+      return result;
+    }
+
+    for &(a, b) in rule.overlaps() {
+      if !merge(&mut result, self.pointing(&rule.groups()[a], &rule.groups()[b])) {
+        return Collapsed::Error;
+      }
+    }
+
+    result
+  }
+
+  fn eliminate(&mut self, index: usize, mask: T) -> Collapsed {
+    let rule = self.rule;
+    let old_value = self.board[index];
+    let new_value = old_value & !mask;
+    if new_value == rule.zero {
+      return Collapsed::Error;
+    }
+    if new_value == old_value {
+      return Collapsed::Unchanged;
+    }
+    self.set_note(index, new_value);
+    Collapsed::Ok
+  }
+
+  fn is_solved_in(&self, group: &[usize], note: T) -> bool {
+    let rule = self.rule;
+    group.iter().any(|&i| self.flags[i] & FLAG_OK == FLAG_OK && self.board[i] & note != rule.zero)
+  }
+
+  // A value that fits only one cell of a full group must go there.
+  fn hidden_single(&mut self, group: &[usize]) -> Collapsed {
+    let rule = self.rule;
+    if group.len() != rule.note.len() {
+      return Collapsed::Unchanged;
+    }
+
+    let mut result = Collapsed::Unchanged;
+    for &note in rule.note.iter() {
+      if self.is_solved_in(group, note) {
+        continue;
+      }
+      let mut found = usize::MAX;
+      let mut count = 0;
+      for &i in group {
+        if self.flags[i] == FLAG_NONE && self.board[i] & note != rule.zero {
+          found = i;
+          count += 1;
+        }
+      }
+      if count == 0 {
+        return Collapsed::Error;
+      }
+      if count == 1 && self.board[found] != note {
+        self.set_note(found, note);
+        result = Collapsed::Ok;
+      }
+    }
+    result
+  }
+
+  fn find_subset(&self, cells: &[usize], size: usize, union: T, chosen: &mut Vec<usize>, found: &mut Vec<(T, Vec<usize>)>) {
+    let rule = self.rule;
+    let count = rule.count(union);
+    if count > size {
+      return;
+    }
+    if chosen.len() == size {
+      found.push((union, chosen.clone()));
+      return;
+    }
+    for (j, &cell) in cells.iter().enumerate() {
+      chosen.push(cell);
+      self.find_subset(&cells[j + 1 ..], size, union | self.board[cell], chosen, found);
+      chosen.pop();
+    }
+  }
+
+  // N cells of a group sharing only N candidates remove those candidates from
+  // the rest of the group.
+  fn naked_subset(&mut self, group: &[usize]) -> Collapsed {
+    let rule = self.rule;
+    let mut cells = vec![];
+    for &i in group {
+      if self.flags[i] == FLAG_NONE && rule.count(self.board[i]) <= MAX_SUBSET {
+        cells.push(i);
+      }
+    }
+
+    let mut found = vec![];
+    for size in 2 ..= MAX_SUBSET {
+      self.find_subset(&cells, size, rule.zero, &mut vec![], &mut found);
+    }
+
+    let mut result = Collapsed::Unchanged;
+    for (union, chosen) in found {
+      if rule.count(union) < chosen.len() {
+        return Collapsed::Error;
+      }
+      for &i in group {
+        if self.flags[i] != FLAG_NONE || chosen.contains(&i) {
+          continue;
+        }
+        if !merge(&mut result, self.eliminate(i, union)) {
+          return Collapsed::Error;
+        }
+      }
+    }
+    result
+  }
+
+  // When every place for a value in a full group lies inside another group,
+  // the value can be removed from the rest of that other group.
+  fn pointing(&mut self, group: &[usize], other: &[usize]) -> Collapsed {
+    let rule = self.rule;
+    if group.len() != rule.note.len() {
+      return Collapsed::Unchanged;
+    }
+
+    let mut result = Collapsed::Unchanged;
+    for &note in rule.note.iter() {
+      if self.is_solved_in(group, note) {
+        continue;
+      }
+      let inside = group.iter().all(|&i| {
+        self.flags[i] != FLAG_NONE || self.board[i] & note == rule.zero || other.contains(&i)
+      });
+      if !inside {
+        continue;
+      }
+      for &i in other {
+        if self.flags[i] != FLAG_NONE || group.contains(&i) {
+          continue;
+        }
+        if !merge(&mut result, self.eliminate(i, note)) {
+          return Collapsed::Error;
+        }
+      }
+    }
+    result
+  }
+
+}
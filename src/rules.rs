@@ -0,0 +1,384 @@
+use super::{
+  alphabet::Alphabet,
+  constraint::{
+    g_adjacent,
+    g_diagonal,
+    g_horse_move,
+    g_king_move,
+    ConstraintListGenerator,
+    GGAntiDiagonal,
+    GGDiagonal,
+    GGHorizontal,
+    GGRegion,
+    GGVertical,
+    GGWindow,
+    GroupGenerator,
+    Kropki,
+    NoConsecutive,
+    NoDuplicate,
+    Xv,
+  },
+  directive::{apply_directive, is_directive, parse_cell, words},
+  error::SudokuError,
+  layout::Layout,
+  sudoku::{Note, Rule},
+};
+
+// A rule file describes a variant, one setting per line, `#` starting a
+// comment:
+//
+//   box 3 3
+//   layout samurai
+//   alphabet digits
+//   region AAABBBCCC
+//   diagonals
+//   windows
+//   anti-knight
+//   anti-king
+//   diagonal-neighbour
+//   non-consecutive
+//   kropki-negative
+//   xv-negative
+//
+// `region` gives one row of the region map per line. Any other line is a
+// directive, like the ones of a puzzle with or without the leading `!`:
+//
+//   killer 15 r1c1 r1c2 r2c1
+//   thermo r1c1 r1c2 r1c3
+//   group r1c1 r2c2 r3c3
+//
+// The negative Kropki and XV rules skip the pairs holding a dot or a mark of
+// any directive, the ones of the puzzle included.
+//
+// A puzzle file may start with a header, the settings of a rule file on lines
+// starting with `%`, making it self-describing. `size` gives the board size in
+// cells, checked against the box size and layout:
+//
+//   %size 9 9
+//   %box 3 3
+//   %diagonals
+//   %killer 15 r1c1 r1c2 r2c1
+
+/// Description of a variant, read from a rule file or filled field by field,
+/// which builds a ready [`Rule`].
+#[derive(Clone, Debug)]
+pub struct RuleSpec {
+  /// Box width in cells.
+  pub width: usize,
+  /// Box height in cells.
+  pub height: usize,
+  /// Name of the [`Layout`].
+  pub layout: String,
+  /// Name of the [`Alphabet`].
+  pub alphabet: Option<String>,
+  /// Region map replacing the boxes, see [`GGRegion::parse`].
+  pub regions: Option<String>,
+  /// No duplicate on the two diagonals of every grid.
+  pub diagonals: bool,
+  /// No duplicate in the hyper sudoku windows of every grid.
+  pub windows: bool,
+  /// No duplicate a knight move apart.
+  pub anti_knight: bool,
+  /// No duplicate a king move apart.
+  pub anti_king: bool,
+  /// No duplicate on diagonally adjacent cells.
+  pub diagonal_neighbour: bool,
+  /// No consecutive values on orthogonally adjacent cells.
+  pub non_consecutive: bool,
+  /// No Kropki relation between adjacent cells without a dot.
+  pub kropki_negative: bool,
+  /// No sum of 5 or 10 between adjacent cells without a mark.
+  pub xv_negative: bool,
+  /// Directives with their line number.
+  pub directives: Vec<(usize, String)>,
+}
+
+impl Default for RuleSpec {
+  fn default() -> RuleSpec {
+    RuleSpec {
+      width: 3,
+      height: 3,
+      layout: "single".to_string(),
+      alphabet: None,
+      regions: None,
+      diagonals: false,
+      windows: false,
+      anti_knight: false,
+      anti_king: false,
+      diagonal_neighbour: false,
+      non_consecutive: false,
+      kropki_negative: false,
+      xv_negative: false,
+      directives: vec![],
+    }
+  }
+}
+
+// Parse the value of a setting, `column` being the one of its keyword when
+// the value is missing.
+fn parse_number(line: usize, column: usize, word: Option<&(usize, &str)>) -> Result<usize, SudokuError> {
+  let &(column, word) = word.unwrap_or(&(column, ""));
+  word.parse::<usize>().map_err(|_| SudokuError::Parse { line, column, text: word.to_string() })
+}
+
+// Whether two lines hold the same words, whatever the spacing.
+fn same_words(a: &str, b: &str) -> bool {
+  words(a).iter().map(|&(_, word)| word).eq(words(b).iter().map(|&(_, word)| word))
+}
+
+/// Whether a line of puzzle text belongs to the header.
+pub fn is_header(line: &str) -> bool {
+  line.trim_start().starts_with('%')
+}
+
+impl RuleSpec {
+  /// Number of values, and cells on the side of a grid.
+  pub fn value_count(&self) -> usize {
+    self.width * self.height
+  }
+
+  /// Apply the settings of a rule file on top of the current ones.
+  pub fn read_str(&mut self, text: &str) -> Result<(), SudokuError> {
+    let mut regions = String::new();
+    let mut size = None;
+    let previous = self.directives.len();
+    for (i, line) in text.lines().enumerate() {
+      let line_number = i + 1;
+      let content = line.split('#').next().unwrap_or("");
+      let list = words(content);
+      let (column, keyword) = match list.first() {
+        Some(&first) => first,
+        None => continue,
+      };
+      match keyword {
+        "box" => {
+          self.width = parse_number(line_number, column, list.get(1))?;
+          self.height = parse_number(line_number, column, list.get(2))?;
+        },
+        "size" => {
+          let width = parse_number(line_number, column, list.get(1))?;
+          let height = parse_number(line_number, column, list.get(2))?;
+          size = Some((line_number, column, width, height));
+        },
+        "layout" | "alphabet" => {
+          let &(column, name) = list.get(1).ok_or(SudokuError::Parse { line: line_number, column, text: keyword.to_string() })?;
+          if keyword == "layout" {
+            if Layout::named(name, 1, 1).is_none() {
+              return Err(SudokuError::Parse { line: line_number, column, text: name.to_string() });
+            }
+            self.layout = name.to_string();
+          } else {
+            self.alphabet = Some(name.to_string());
+          }
+        },
+        "region" => {
+          regions += &list[1 ..].iter().map(|&(_, word)| word).collect::<Vec<_>>().join(" ");
+          regions.push('\n');
+        },
+        "diagonals" => self.diagonals = true,
+        "windows" => self.windows = true,
+        "anti-knight" => self.anti_knight = true,
+        "anti-king" => self.anti_king = true,
+        "diagonal-neighbour" => self.diagonal_neighbour = true,
+        "non-consecutive" => self.non_consecutive = true,
+        "kropki-negative" => self.kropki_negative = true,
+        "xv-negative" => self.xv_negative = true,
+        _ => self.add_directive(previous, line_number, content),
+      }
+    }
+    if !regions.is_empty() {
+      self.regions = Some(regions);
+    }
+    if let Some((line, column, width, height)) = size {
+      let layout = self.layout()?;
+      if (layout.width, layout.height) != (width, height) {
+        return Err(SudokuError::Parse { line, column, text: format!("size {} {}", width, height) });
+      }
+    }
+    Ok(())
+  }
+
+  /// Add the directives of a puzzle, its lines starting with `!`.
+  pub fn read_directives(&mut self, text: &str) {
+    let previous = self.directives.len();
+    for (i, line) in text.lines().enumerate() {
+      if is_directive(line) {
+        self.add_directive(previous, i + 1, line);
+      }
+    }
+  }
+
+  // Add the directive `text` found at `line`, keeping the columns of the
+  // original line. A directive among the first `previous` ones, read before,
+  // such as one of the rule file repeated in the puzzle, is only applied once.
+  fn add_directive(&mut self, previous: usize, line: usize, text: &str) {
+    let directive = text.replacen('!', " ", 1);
+    if !self.directives[.. previous].iter().any(|(_, other)| same_words(other, &directive)) {
+      self.directives.push((line, directive));
+    }
+  }
+
+  /// Apply the header of a puzzle file on top of the current settings.
+  pub fn read_header(&mut self, text: &str) -> Result<(), SudokuError> {
+    if !text.lines().any(is_header) {
+      return Ok(());
+    }
+    // Blank the other lines out, keeping line numbers and columns.
+    let lines: Vec<String> = text.lines()
+      .map(|line| if is_header(line) { line.replacen('%', " ", 1) } else { String::new() })
+      .collect();
+    self.read_str(&lines.join("\n"))
+  }
+
+  /// Header describing the rule, see [`RuleSpec::read_header`].
+  pub fn to_header(&self) -> String {
+    let mut result = String::new();
+    if let Ok(layout) = self.layout() {
+      result += &format!("%size {} {}\n", layout.width, layout.height);
+    }
+    result += &format!("%box {} {}\n", self.width, self.height);
+    if self.layout != "single" {
+      result += &format!("%layout {}\n", self.layout);
+    }
+    if let Some(name) = &self.alphabet {
+      result += &format!("%alphabet {}\n", name);
+    }
+    if let Some(regions) = &self.regions {
+      for line in regions.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        result += &format!("%region {}\n", line);
+      }
+    }
+    let flags = [
+      ("diagonals", self.diagonals),
+      ("windows", self.windows),
+      ("anti-knight", self.anti_knight),
+      ("anti-king", self.anti_king),
+      ("diagonal-neighbour", self.diagonal_neighbour),
+      ("non-consecutive", self.non_consecutive),
+      ("kropki-negative", self.kropki_negative),
+      ("xv-negative", self.xv_negative),
+    ];
+    for (name, _) in flags.iter().filter(|&&(_, on)| on) {
+      result += &format!("%{}\n", name);
+    }
+    for (_, text) in self.directives.iter() {
+      result += &format!("%{}\n", text.trim());
+    }
+    result
+  }
+
+  /// Layout of the grids on the board.
+  pub fn layout(&self) -> Result<Layout, SudokuError> {
+    Layout::named(&self.layout, self.width, self.height)
+      .ok_or(SudokuError::Unsupported(format!("unknown layout {}", self.layout)))
+  }
+
+  /// Regions of the region map, if any.
+  pub fn regions<N: Note>(&self, nodup: &ConstraintListGenerator<NoDuplicate, N>) -> Result<Option<GGRegion>, SudokuError> {
+    let text = match &self.regions {
+      Some(text) => text,
+      None => return Ok(None),
+    };
+    if self.layout != "single" {
+      return Err(SudokuError::Unsupported("regions only work with a single grid".to_string()));
+    }
+    let regions = GGRegion::parse(nodup, text)?;
+    let (_, region_size) = regions.get_size();
+    if region_size != self.value_count() {
+      return Err(SudokuError::InvalidRegions(format!(
+        "regions have {} cells instead of {}", region_size, self.value_count()
+      )));
+    }
+    Ok(Some(regions))
+  }
+
+  /// Rows, columns and boxes (or `regions`) of every grid of the layout, and
+  /// the extra groups.
+  pub fn group_generators<N: Note>(&self, layout: &Layout, nodup: &ConstraintListGenerator<NoDuplicate, N>, regions: Option<GGRegion>) -> Result<Vec<Box<dyn GroupGenerator>>, SudokuError> {
+    let (width, height) = (self.width, self.height);
+    let size = layout.grid_size();
+    let mut result: Vec<Box<dyn GroupGenerator>> = match regions {
+      Some(regions) => vec![
+        Box::new(GGVertical::new(nodup, size, size, 0, 0)),
+        Box::new(GGHorizontal::new(nodup, size, size, 0, 0)),
+        Box::new(regions),
+      ],
+      None => layout.group_generators(nodup),
+    };
+    for &(dx, dy) in layout.grids() {
+      if self.diagonals {
+        result.push(Box::new(GGDiagonal::new(nodup, size, dx, dy)?));
+        result.push(Box::new(GGAntiDiagonal::new(nodup, size, dx, dy)?));
+      }
+      if self.windows {
+        let windows = GGWindow::new(nodup, height, width, width, height, dx, dy);
+        if !windows.fits() {
+          return Err(SudokuError::Unsupported(format!("windows don't fit boxes of {}x{}", width, height)));
+        }
+        result.push(Box::new(windows));
+      }
+    }
+    Ok(result)
+  }
+
+  /// Build the rule, using `one` as the note of the first value.
+  pub fn build<N: Note>(&self, one: N) -> Result<Rule<N>, SudokuError> {
+    let layout = self.layout()?;
+    let mut rule = layout.rule(one)?;
+    if let Some(name) = &self.alphabet {
+      rule.set_alphabet(Alphabet::named(name, layout.grid_size())?)?;
+    }
+
+    let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+    let regions = self.regions(&nodup)?;
+    if let Some(regions) = &regions {
+      rule.set_regions(regions.map().to_vec())?;
+    }
+    for gen in self.group_generators(&layout, &nodup, regions)? {
+      nodup.add_group_generator(gen.as_ref())?;
+    }
+    if self.anti_knight {
+      nodup.add_every(g_horse_move);
+    }
+    if self.anti_king {
+      nodup.add_every(g_king_move);
+    }
+    if self.diagonal_neighbour {
+      nodup.add_every(g_diagonal);
+    }
+    nodup.apply(&mut rule)?;
+
+    if self.non_consecutive {
+      let mut nocons = ConstraintListGenerator::new(NoConsecutive::new(), &rule);
+      nocons.add_every(g_adjacent);
+      nocons.apply(&mut rule)?;
+    }
+
+    let mut dots = vec![];
+    let mut marks = vec![];
+    for (line, text) in self.directives.iter() {
+      let words: Vec<&str> = text.split_whitespace().collect();
+      if let [name, a, b] = words[..] {
+        if let (Some(a), Some(b)) = (parse_cell(&rule, a), parse_cell(&rule, b)) {
+          match name {
+            "white" | "black" => dots.push((a, b)),
+            "x" | "v" => marks.push((a, b)),
+            _ => {},
+          }
+        }
+      }
+      apply_directive(&mut rule, *line, text)?;
+    }
+    if self.kropki_negative {
+      let mut negative = ConstraintListGenerator::new(Kropki::negative(dots), &rule);
+      negative.add_every(g_adjacent);
+      negative.apply(&mut rule)?;
+    }
+    if self.xv_negative {
+      let mut negative = ConstraintListGenerator::new(Xv::negative(marks), &rule);
+      negative.add_every(g_adjacent);
+      negative.apply(&mut rule)?;
+    }
+    Ok(rule)
+  }
+}
@@ -0,0 +1,84 @@
+use super::sudoku::{FLAG_NONE, Note, Sudoku};
+
+/// Decide which unsolved cell the solver branches on next.
+pub trait CellSelector<T> {
+  /// Return the chosen cell, or `usize::MAX` when there is no unsolved cell.
+  fn select(&self, sudoku: &Sudoku<T>) -> usize;
+}
+
+/// Branch on the first unsolved cell in board order.
+pub struct FirstUnsolved;
+
+/// Branch on the unsolved cell with the fewest candidates.
+pub struct MinRemaining;
+
+/// Like [`MinRemaining`], but break ties by the number of unsolved neighbours.
+pub struct MinRemainingDegree;
+
+fn degree<T: Note>(sudoku: &Sudoku<T>, index: usize) -> usize {
+  let mut result = 0;
+  for constr in sudoku.rule.constraints(index) {
+    for &neighbour in constr.neighbours() {
+      if sudoku.flags[neighbour] == FLAG_NONE {
+        result += 1;
+      }
+    }
+  }
+  result
+}
+
+impl<T: Note> CellSelector<T> for FirstUnsolved {
+  fn select(&self, sudoku: &Sudoku<T>) -> usize {
+    for i in 0 .. sudoku.rule.size {
+      if sudoku.flags[i] == FLAG_NONE {
+        return i;
+      }
+    }
+    usize::MAX
+  }
+}
+
+impl<T: Note> CellSelector<T> for MinRemaining {
+  fn select(&self, sudoku: &Sudoku<T>) -> usize {
+    let rule = sudoku.rule;
+    let mut best = usize::MAX;
+    let mut best_count = usize::MAX;
+    for i in 0 .. rule.size {
+      if sudoku.flags[i] != FLAG_NONE {
+        continue;
+      }
+      let count = rule.count(sudoku.board[i]);
+      if count < best_count {
+        best = i;
+        best_count = count;
+        if count <= 2 {
+          break;
+        }
+      }
+    }
+    best
+  }
+}
+
+impl<T: Note> CellSelector<T> for MinRemainingDegree {
+  fn select(&self, sudoku: &Sudoku<T>) -> usize {
+    let rule = sudoku.rule;
+    let mut best = usize::MAX;
+    let mut best_score = (usize::MAX, 0);
+    for i in 0 .. rule.size {
+      if sudoku.flags[i] != FLAG_NONE {
+        continue;
+      }
+      let count = rule.count(sudoku.board[i]);
+      if count > best_score.0 {
+        continue;
+      }
+      let score = (count, degree(sudoku, i));
+      if score.0 < best_score.0 || score.1 > best_score.1 {
+        best = i;
+        best_score = score;
+      }
+    }
+    best
+  }
+}
@@ -1,8 +1,11 @@
-#![allow(dead_code)]
-
-use std::{cmp, fmt::{self, Display, Formatter}, fs::File, io::{self, BufReader, Read}, ops::{BitAnd, BitOr, Not, Shl, Shr}};
+use std::{cmp, sync::Arc, fmt::{self, Display, Formatter}, fs::File, io::{BufReader, Read}, ops::{BitAnd, BitOr, Not, Shl, Shr}};
 use rand::{thread_rng, seq::SliceRandom};
+use crate::{alphabet::Alphabet, directive::is_directive, error::SudokuError, rules::is_header, selector::{CellSelector, MinRemaining}};
 
+/// Bit set of candidates of a cell, one bit per value.
+///
+/// Implemented for every integer type; the type must have at least as many
+/// bits as the largest value of the rule.
 pub trait Note
   : Shl<i32, Output = Self>
   + Shr<i32, Output = Self>
@@ -24,43 +27,118 @@ where T
   + Copy
 {}
 
+/// Outcome of propagating a change through the constraints.
 pub enum Collapsed {
+  /// Some candidates were removed.
   Ok,
+  /// Nothing changed.
   Unchanged,
+  /// A cell ran out of candidates.
   Error,
 }
 
-pub trait Constraint<T> {
-  fn added(&self, sudoku: &mut Sudoku<T>, index: usize) -> ();
+/// Rule attached to a cell, consulted whenever the cell collapses.
+///
+/// Implementations must change candidates through [`Sudoku::set_note`] so
+/// the search can undo them. A constraint may be shared by several cells.
+pub trait Constraint<T>: Send + Sync {
+  /// Called once for every cell the constraint is attached to when a
+  /// [`Sudoku`] is created.
+  fn added(&self, sudoku: &mut Sudoku<T>, index: usize);
+  /// Called when the cell at `index` is set to `value` (`note` is its bit).
   fn collapsed(&self, sudoku: &mut Sudoku<T>, index: usize, value: usize, note: T) -> Collapsed;
+
+  /// Whether [`Constraint::changed`] should be called when candidates of the
+  /// cells it's attached to change.
+  fn watch_changes(&self) -> bool {
+    false
+  }
+
+  /// Called after candidates of the cell at `index` were removed without
+  /// collapsing it. Only called when [`Constraint::watch_changes`] is true.
+  fn changed(&self, _sudoku: &mut Sudoku<T>, _index: usize) -> Collapsed {
+    Collapsed::Unchanged
+  }
+
+  /// Name used when reporting a violation.
+  fn name(&self) -> &str {
+    "constraint"
+  }
+
+  /// Solved cell that directly forbids setting `value` at `index`, if any.
+  fn conflict(&self, _sudoku: &Sudoku<T>, _index: usize, _value: usize) -> Option<usize> {
+    None
+  }
+
+  /// Cells whose candidates may be affected when this cell collapses.
+  fn neighbours(&self) -> &[usize] {
+    &[]
+  }
+
+  /// Whether this constraint only forbids its neighbours from sharing the
+  /// collapsed value.
+  fn distinct(&self) -> bool {
+    false
+  }
 }
 
-pub struct  Rule<'a, T = u32> {
+/// Shape of the board and the constraints attached to each cell.
+#[derive(Clone)]
+pub struct  Rule<T = u32> {
+  /// Board width in cells.
   pub width: usize,
+  /// Board height in cells.
   pub height: usize,
+  /// Number of cells, `width * height`.
   pub size: usize,
   x_split: usize,
   y_split: usize,
+  // Region of every cell, drawn instead of the grid when not empty.
+  regions: Vec<usize>,
+  // Cells outside of the puzzle, such as the gaps of a Samurai.
+  ignored: Vec<bool>,
+  alphabet: Alphabet,
+  /// Note without any candidate.
   pub zero: T,
+  /// Note with every value as candidate.
   pub all: T,
+  /// Note of each value, `note[value]` only has the bit of `value`.
   pub note: Vec<T>,
-  constraints: Vec<Vec<&'a dyn Constraint<T>>>,
+  constraints: Vec<Vec<Arc<dyn Constraint<T>>>>,
+  // Whether a constraint of the cell watches partial changes.
+  watched: Vec<bool>,
+  groups: Vec<Vec<usize>>,
+  // Pairs of groups sharing at least two cells.
+  overlaps: Vec<(usize, usize)>,
 }
 
+/// State of a cell, a combination of the `FLAG_*` bits.
 pub type Flag = u8;
+/// Cell is not solved yet.
 pub const FLAG_NONE: Flag = 0;
+/// Cell is solved.
 pub const FLAG_OK: Flag = 1;
+/// Cell is not part of the board.
 pub const FLAG_IGNORED: Flag = 2;
+/// Cell is a given of the puzzle.
 pub const FLAG_FIXED: Flag = 4;
 
+/// Board state of a puzzle played under a [`Rule`].
 #[derive(Clone)]
 pub struct Sudoku<'a, T> {
-  pub rule: &'a Rule<'a, T>,
+  pub rule: &'a Rule<T>,
+  /// Candidates of each cell.
   pub board: Vec<T>,
+  /// State of each cell.
   pub flags: Vec<Flag>,
+  selector: &'a dyn CellSelector<T>,
+  // Previous state of every changed cell, used to undo the search.
+  trail: Vec<(usize, T, Flag)>,
+  // Watched cells whose candidates changed since the last propagation.
+  changed: Vec<usize>,
 }
 
-fn get_note_index<T>(note: &Vec<T>, value: T) -> usize
+fn get_note_index<T>(note: &[T], value: T) -> usize
 where T: cmp::PartialEq<T>
 {
   for (i, x) in note.iter().enumerate() {
@@ -68,54 +146,190 @@ where T: cmp::PartialEq<T>
       return i
     }
   }
-  return usize::MAX;
+  usize::MAX
 }
 
-impl<'a, T: Note> Rule<'a, T> {
-  pub fn new(width: usize, height: usize, max_value: u32, one: T) -> Rule<'a, T> {
+impl<T: Note> Rule<T> {
+  /// Create a rule for a `width` x `height` board holding values
+  /// `1 ..= max_value`. `one` is the note of the first value, usually `1`.
+  pub fn new(width: usize, height: usize, max_value: u32, one: T) -> Result<Rule<T>, SudokuError> {
     let size = width * height;
+    let zero = (one << 1) & one;
     let mut all = one;
     let mut curr = one;
     let mut note = vec![one];
 
+    if one == zero {
+      return Err(SudokuError::RuleTooLarge { max_value });
+    }
     for _ in 1 .. max_value {
       curr = curr << 1;
+      if curr == zero {
+        return Err(SudokuError::RuleTooLarge { max_value });
+      }
       all = all | curr;
       note.push(curr);
     }
 
-    Rule {
+    Ok(Rule {
       width,
       height,
       size,
       x_split: 3,
       y_split: 3,
+      regions: vec![],
+      ignored: vec![false; size],
+      alphabet: Alphabet::Decimal,
       // max: max_value,
-      zero: (one << 1) & one,
+      zero,
       all,
       note,
-      constraints: vec![vec![]; size]
+      constraints: vec![vec![]; size],
+      watched: vec![false; size],
+      groups: vec![],
+      overlaps: vec![],
+    })
+  }
+
+  fn check_index(&self, index: usize) -> Result<(), SudokuError> {
+    if index >= self.size {
+      return Err(SudokuError::InvalidIndex { index, size: self.size });
     }
+    Ok(())
   }
 
-  pub fn add_constraint(&mut self, index: usize, constraint: &'a dyn Constraint<T>) {
+  /// Attach a constraint to the cell at `index`. The same constraint can be
+  /// attached to several cells.
+  pub fn add_constraint(&mut self, index: usize, constraint: Arc<dyn Constraint<T>>) -> Result<(), SudokuError> {
+    self.check_index(index)?;
+    self.watched[index] |= constraint.watch_changes();
     self.constraints[index].push(constraint);
+    Ok(())
   }
 
+  /// Register a set of cells which can't contain duplicate value, so the
+  /// solver can reason about the group as a whole.
+  pub fn add_group(&mut self, group: Vec<usize>) -> Result<(), SudokuError> {
+    for &index in group.iter() {
+      self.check_index(index)?;
+    }
+    if self.groups.contains(&group) {
+      return Ok(());
+    }
+    let index = self.groups.len();
+    for (i, other) in self.groups.iter().enumerate() {
+      let shared = group.iter().filter(|x| other.contains(x)).count();
+      if shared >= 2 {
+        self.overlaps.push((i, index));
+        self.overlaps.push((index, i));
+      }
+    }
+    self.groups.push(group);
+    Ok(())
+  }
+
+  /// Groups registered with [`Rule::add_group`].
+  pub fn groups(&self) -> &[Vec<usize>] {
+    &self.groups
+  }
+
+  /// Pairs of group indices sharing at least two cells.
+  pub fn overlaps(&self) -> &[(usize, usize)] {
+    &self.overlaps
+  }
+
+  /// Size of the box used to separate cells when printing.
   pub fn set_grid(&mut self, width: usize, height: usize) {
     self.x_split = width;
     self.y_split = height;
   }
 
+  /// Region of every cell, such as [`crate::constraint::GGRegion::map`]. When
+  /// set, borders between regions are printed instead of the grid.
+  pub fn set_regions(&mut self, regions: Vec<usize>) -> Result<(), SudokuError> {
+    if regions.len() != self.size {
+      return Err(SudokuError::CellCount { expected: self.size, found: regions.len() });
+    }
+    self.regions = regions;
+    Ok(())
+  }
+
+  /// Symbols used to read and print values. Symbol alphabets must have one
+  /// symbol per value.
+  pub fn set_alphabet(&mut self, alphabet: Alphabet) -> Result<(), SudokuError> {
+    if let Some(count) = alphabet.count() {
+      if count != self.note.len() {
+        return Err(SudokuError::InvalidAlphabet(format!("{} symbols for {} values", count, self.note.len())));
+      }
+    }
+    self.alphabet = alphabet;
+    Ok(())
+  }
+
+  /// Symbols used to read and print values.
+  pub fn alphabet(&self) -> &Alphabet {
+    &self.alphabet
+  }
+
+  /// Attach one constraint shared by every cell of `cells`.
+  pub fn add_shared_constraint(&mut self, cells: &[usize], constraint: Arc<dyn Constraint<T>>) -> Result<(), SudokuError> {
+    for &index in cells {
+      self.add_constraint(index, constraint.clone())?;
+    }
+    Ok(())
+  }
+
+  /// Leave the cell at `index` out of the puzzle. Boards of the rule start
+  /// with the cell ignored, and readers and printers skip it.
+  pub fn ignore_cell(&mut self, index: usize) -> Result<(), SudokuError> {
+    self.check_index(index)?;
+    self.ignored[index] = true;
+    Ok(())
+  }
+
+  /// Whether the cell at `index` is left out of the puzzle.
+  pub fn is_ignored(&self, index: usize) -> bool {
+    self.ignored[index]
+  }
+
+  /// Constraints attached to the cell at `index`.
+  pub fn constraints(&self, index: usize) -> &[Arc<dyn Constraint<T>>] {
+    &self.constraints[index]
+  }
+
+  /// Value of a note holding a single candidate, or None.
+  pub fn value(&self, note: T) -> Option<usize> {
+    match get_note_index(&self.note, note) {
+      usize::MAX => None,
+      value => Some(value),
+    }
+  }
+
+  /// Number of candidates in `note`.
+  pub fn count(&self, mut note: T) -> usize {
+    let mut result = 0;
+    while note != self.zero {
+      if (note & self.note[0]) != self.zero {
+        result += 1;
+      }
+      note = note >> 1;
+    }
+    result
+  }
+
 }
 
 impl<'s, T: Note> Sudoku<'s, T> {
 
+  /// Create an empty board for `rule`.
   pub fn new<'a>(rule: &'a Rule<T>) -> Sudoku<'a, T> {
     let mut result = Sudoku {
       rule,
       board: vec![rule.all; rule.size],
-      flags: vec![0; rule.size],
+      flags: rule.ignored.iter().map(|&ignored| if ignored { FLAG_IGNORED } else { FLAG_NONE }).collect(),
+      selector: &MinRemaining,
+      trail: vec![],
+      changed: vec![],
     };
 
     for i in 0 .. rule.size {
@@ -123,9 +337,104 @@ impl<'s, T: Note> Sudoku<'s, T> {
         constr.added(&mut result, i)
       }
     }
+    result.trail.clear();
+    result
+  }
+
+  /// Copy of the current state without the undo history.
+  pub fn snapshot(&self) -> Sudoku<'s, T> {
+    Sudoku {
+      rule: self.rule,
+      board: self.board.clone(),
+      flags: self.flags.clone(),
+      selector: self.selector,
+      trail: vec![],
+      changed: vec![],
+    }
+  }
+
+  /// Position in the undo history to pass to [`Sudoku::rollback`].
+  pub fn mark(&self) -> usize {
+    self.trail.len()
+  }
+
+  /// Undo every change made since `mark` was taken.
+  pub fn rollback(&mut self, mark: usize) {
+    while self.trail.len() > mark {
+      if let Some((index, note, flag)) = self.trail.pop() {
+        self.board[index] = note;
+        self.flags[index] = flag;
+      }
+    }
+  }
+
+  fn save(&mut self, index: usize) {
+    self.trail.push((index, self.board[index], self.flags[index]));
+  }
+
+  /// Change the candidates of a cell, recording it so it can be rolled back.
+  /// Constraints must write through this instead of `board`.
+  pub fn set_note(&mut self, index: usize, note: T) {
+    if self.board[index] != note {
+      self.save(index);
+      self.board[index] = note;
+      if self.rule.watched[index] && self.changed.last() != Some(&index) {
+        self.changed.push(index);
+      }
+    }
+  }
+
+  /// Keep only the `allowed` candidates of a cell, setting `result` to
+  /// [`Collapsed::Ok`] if they change. Return false when no candidate is left
+  /// or a collapsed cell would change. Cells outside of the board are left
+  /// alone.
+  pub(crate) fn restrict(&mut self, index: usize, allowed: T, result: &mut Collapsed) -> bool {
+    if self.flags[index] & FLAG_IGNORED == FLAG_IGNORED {
+      return true;
+    }
+    let old_value = self.board[index];
+    let new_value = old_value & allowed;
+    if new_value == self.rule.zero {
+      return false;
+    }
+    if new_value != old_value {
+      if self.flags[index] != FLAG_NONE {
+        return false;
+      }
+      self.set_note(index, new_value);
+      *result = Collapsed::Ok;
+    }
+    true
+  }
+
+  // Let watching constraints prune after partial changes.
+  fn propagate_changes(&mut self) -> Collapsed {
+    let rule = self.rule;
+    let mut result = Collapsed::Unchanged;
+    while let Some(index) = self.changed.pop() {
+      for constr in rule.constraints[index].iter() {
+        if !constr.watch_changes() {
+          continue;
+        }
+        match constr.changed(self, index) {
+          Collapsed::Ok => result = Collapsed::Ok,
+          Collapsed::Error => {
+            self.changed.clear();
+            return Collapsed::Error;
+          },
+          Collapsed::Unchanged => {},
+        }
+      }
+    }
     result
   }
 
+  /// Change how the solver picks the cell to branch on.
+  pub fn set_selector(&mut self, selector: &'s dyn CellSelector<T>) {
+    self.selector = selector;
+  }
+
+  /// Mark every solved cell as a given.
   pub fn make_fixed(&mut self) {
     for i in 0 .. self.rule.size {
       if self.flags[i] & FLAG_OK == FLAG_OK {
@@ -134,12 +443,17 @@ impl<'s, T: Note> Sudoku<'s, T> {
     }
   }
 
+  /// Set the cell at `index` to `value` and propagate it to its constraints.
   pub fn collapse(&mut self, index: usize, value: usize) -> Collapsed {
     let rule = self.rule;
     if self.flags[index] & FLAG_OK == FLAG_OK {
       return Collapsed::Unchanged;
     }
     let note = rule.note[value];
+    if self.board[index] & note == rule.zero {
+      return Collapsed::Error;
+    }
+    self.save(index);
     self.flags[index] |= FLAG_OK;
     self.board[index] = note;
 
@@ -155,11 +469,15 @@ impl<'s, T: Note> Sudoku<'s, T> {
     result
   }
 
+  /// Propagate every naked single and group deduction, then return the cell
+  /// to branch on, or `usize::MAX` when every cell is solved. Return None on
+  /// contradiction.
   pub fn collapse_avail(&mut self) -> Option<usize> {
     let rule = self.rule;
-    let mut first_unsolved ;
     'repeat: loop {
-      first_unsolved = usize::MAX;
+      if let Collapsed::Error = self.propagate_changes() {
+        return None;
+      }
       for i in 0 .. rule.size {
         // Skip if ignored or ok.
         if self.flags[i] != FLAG_NONE {
@@ -171,9 +489,7 @@ impl<'s, T: Note> Sudoku<'s, T> {
           return None;
         }
         let value = get_note_index(&rule.note, note);
-        if value == usize::MAX {
-          first_unsolved = i;
-        } else {
+        if value != usize::MAX {
           match self.collapse(i, value) {
             Collapsed::Ok => continue 'repeat,
             Collapsed::Error => return None,
@@ -181,12 +497,20 @@ impl<'s, T: Note> Sudoku<'s, T> {
           }
         }
       }
+      match self.propagate_groups() {
+        Collapsed::Ok => continue 'repeat,
+        Collapsed::Error => return None,
+        Collapsed::Unchanged => {},
+      }
+      if !self.changed.is_empty() {
+        continue 'repeat;
+      }
       break;
     }
-    return Some(first_unsolved);
+    Some(self.selector.select(self))
   }
 
-  pub fn count_solution(&mut self, limit: usize) -> usize {
+  fn count_recursive(&mut self, limit: usize) -> usize {
     let rule = self.rule;
     let first_unsolved = match self.collapse_avail() {
       None => return 0,
@@ -199,11 +523,12 @@ impl<'s, T: Note> Sudoku<'s, T> {
     let mut result: usize = 0;
     while note != rule.zero && result < limit {
       if (note & rule.note[0]) != rule.zero {
-        let mut copy = self.clone();
-        match copy.collapse(first_unsolved, value) {
+        let mark = self.mark();
+        match self.collapse(first_unsolved, value) {
           Collapsed::Error => {},
-          _ => result += copy.count_solution(limit),
+          _ => result += self.count_recursive(limit),
         }
+        self.rollback(mark);
       }
       note = note >> 1;
       value += 1;
@@ -212,11 +537,22 @@ impl<'s, T: Note> Sudoku<'s, T> {
     result
   }
 
-  fn solve_random_recursive(&mut self, dst: &mut Vec<Sudoku<'s, T>>, limit: usize) -> bool {
+  /// Count solutions, stopping once `limit` is reached.
+  pub fn count_solution(&mut self, limit: usize) -> usize {
+    let mark = self.mark();
+    let result = self.count_recursive(limit);
+    self.rollback(mark);
+    result
+  }
+
+  fn solve_random_recursive(&mut self, dst: &mut Vec<Sudoku<'s, T>>, limit: usize) {
     let rule = self.rule;
     let first_unsolved = match self.collapse_avail() {
-      None => return false,
-      Some(usize::MAX) => return true,
+      None => return,
+      Some(usize::MAX) => {
+        dst.push(self.snapshot());
+        return;
+      },
       Some(x) => x,
     };
 
@@ -233,33 +569,35 @@ impl<'s, T: Note> Sudoku<'s, T> {
     candidates.shuffle(&mut thread_rng());
 
     while dst.len() < limit {
-      let mut copy = self.clone();
       match candidates.pop() {
         None => break,
-        Some(value) => match copy.collapse(first_unsolved, value) {
-          Collapsed::Error => {},
-          _ => if Sudoku::solve_random_recursive(&mut copy, dst, limit) {
-            dst.push(copy);
-          },
+        Some(value) => {
+          let mark = self.mark();
+          match self.collapse(first_unsolved, value) {
+            Collapsed::Error => {},
+            _ => self.solve_random_recursive(dst, limit),
+          }
+          self.rollback(mark);
         },
       }
     }
-
-    return false;
   }
 
+  /// Like [`Sudoku::solve`], but try candidates in random order.
   pub fn solve_random(&mut self, dst: &mut Vec<Sudoku<'s, T>>, limit: usize) {
-    let mut copy = self.clone();
-    if copy.solve_random_recursive(dst, limit) {
-      dst.push(copy);
-    }
+    let mark = self.mark();
+    self.solve_random_recursive(dst, limit);
+    self.rollback(mark);
   }
 
-  fn solve_recursive(&mut self, dst: &mut Vec<Sudoku<'s, T>>, limit: usize) -> bool {
+  fn solve_recursive(&mut self, dst: &mut Vec<Sudoku<'s, T>>, limit: usize) {
     let rule = self.rule;
     let first_unsolved = match self.collapse_avail() {
-      None => return false,
-      Some(usize::MAX) => return true,
+      None => return,
+      Some(usize::MAX) => {
+        dst.push(self.snapshot());
+        return;
+      },
       Some(x) => x,
     };
 
@@ -267,54 +605,180 @@ impl<'s, T: Note> Sudoku<'s, T> {
     let mut value = 0;
     while note != rule.zero && dst.len() < limit {
       if (note & rule.note[0]) != rule.zero {
-        let mut copy = self.clone();
-        match copy.collapse(first_unsolved, value) {
+        let mark = self.mark();
+        match self.collapse(first_unsolved, value) {
           Collapsed::Error => {},
-          _ => if Sudoku::solve_recursive(&mut copy, dst, limit) {
-            dst.push(copy);
-          }
+          _ => self.solve_recursive(dst, limit),
         }
+        self.rollback(mark);
       }
       note = note >> 1;
       value += 1;
     }
-
-    false
   }
 
+  /// Push solutions into `dst` until it holds `limit` of them.
   pub fn solve(&mut self, dst: &mut Vec<Sudoku<'s, T>>, limit: usize) {
-    let mut copy = self.clone();
-    if copy.solve_recursive(dst, limit) {
-      dst.push(copy);
-    }
+    let mark = self.mark();
+    self.solve_recursive(dst, limit);
+    self.rollback(mark);
   }
 
+  /// Exclude the cell at `index` from the board.
   pub fn ignore(&mut self, index: usize) {
+    self.save(index);
     self.flags[index] |= FLAG_IGNORED;
   }
 
-  pub fn read_from_file(&mut self, filename: &str) -> io::Result<()> {
+  fn position(&self, index: usize) -> (usize, usize) {
+    (index / self.rule.width + 1, index % self.rule.width + 1)
+  }
+
+  // Collapse a given, reporting which given and constraint it conflicts with.
+  fn check_given(&mut self, index: usize, value: usize) -> Result<(), SudokuError> {
+    let rule = self.rule;
+    for constr in rule.constraints[index].iter() {
+      if let Some(other) = constr.conflict(self, index, value) {
+        return Err(SudokuError::Contradiction {
+          cell: self.position(index),
+          value: value + 1,
+          other: Some(self.position(other)),
+          constraint: Some(constr.name().to_string()),
+        });
+      }
+    }
+    if let Collapsed::Error = self.collapse(index, value) {
+      return Err(SudokuError::Contradiction {
+        cell: self.position(index),
+        value: value + 1,
+        other: None,
+        constraint: None,
+      });
+    }
+    Ok(())
+  }
+
+  /// Read the givens from a file, see [`Sudoku::read_from`].
+  pub fn read_from_file(&mut self, filename: &str) -> Result<(), SudokuError> {
     let mut file = File::open(filename)?;
     self.read_from(&mut file)
   }
 
-  pub fn read_from(&mut self, src: &mut dyn Read) -> io::Result<()> {
+  /// Read the givens in board order. Symbols of the rule alphabet are
+  /// values, `.` is an empty cell and everything else is ignored. With the
+  /// decimal alphabet a run of digits is one value. Stop once the board is
+  /// full. Givens breaking a constraint are reported as
+  /// [`SudokuError::Contradiction`].
+  pub fn read_from(&mut self, src: &mut dyn Read) -> Result<(), SudokuError> {
     let mut reader = BufReader::new(src);
 
     let mut buffer = String::new();
 
     reader.read_to_string(&mut buffer)?;
+    self.read_str(&buffer)
+  }
+
+  /// Read the givens from text, see [`Sudoku::read_from`]. Directive lines
+  /// (starting with `!`) and header lines (starting with `%`) are skipped.
+  pub fn read_str(&mut self, text: &str) -> Result<(), SudokuError> {
+    // Blank directive and header lines out, keeping line numbers for errors.
+    let buffer: Vec<&str> = text.lines()
+      .map(|line| if is_directive(line) || is_header(line) { "" } else { line })
+      .collect();
+    let buffer = buffer.join("\n");
+
+    match self.rule.alphabet {
+      Alphabet::Decimal => self.read_decimal(&buffer),
+      Alphabet::Symbols(_) => self.read_symbols(&buffer),
+    }
+  }
+
+  /// Read the givens from a single line holding one symbol per cell, with
+  /// `.` or `0` for empty cells. Ignored cells are skipped.
+  pub fn read_line(&mut self, line: &str) -> Result<(), SudokuError> {
+    let rule = self.rule;
+    let cells: Vec<usize> = (0 .. rule.size)
+      .filter(|&i| self.flags[i] & FLAG_IGNORED != FLAG_IGNORED)
+      .collect();
+    let line = line.trim();
+    let found = line.chars().count();
+    if found != cells.len() {
+      return Err(SudokuError::CellCount { expected: cells.len(), found });
+    }
+
+    for (column, (ch, &i)) in line.chars().zip(cells.iter()).enumerate() {
+      match rule.alphabet.value(ch) {
+        Some(value) if value < rule.note.len() => self.check_given(i, value)?,
+        _ if ch == '.' || ch == '0' => {},
+        _ => return Err(SudokuError::Parse { line: 1, column: column + 1, text: ch.to_string() }),
+      }
+    }
+    Ok(())
+  }
+
+  /// Write the board as a single line, see [`Sudoku::read_line`].
+  pub fn to_line(&self) -> String {
+    let rule = self.rule;
+    let mut result = String::new();
+    for i in 0 .. rule.size {
+      if self.flags[i] & FLAG_IGNORED == FLAG_IGNORED {
+        continue;
+      }
+      match get_note_index(&rule.note, self.board[i]) {
+        n if n != usize::MAX && self.flags[i] != FLAG_NONE => result += &rule.alphabet.symbol(n),
+        _ => result.push('.'),
+      }
+    }
+    result
+  }
+
+  // First cell from `i` which isn't ignored.
+  fn skip_ignored(&self, mut i: usize) -> usize {
+    while i < self.rule.size && self.flags[i] & FLAG_IGNORED == FLAG_IGNORED {
+      i += 1;
+    }
+    i
+  }
+
+  fn read_symbols(&mut self, buffer: &str) -> Result<(), SudokuError> {
+    let rule = self.rule;
+    let mut i = 0;
+    for ch in buffer.chars() {
+      i = self.skip_ignored(i);
+      if i >= rule.size {
+        break;
+      }
+      if ch == '.' {
+        i += 1;
+      } else if let Some(value) = rule.alphabet.value(ch) {
+        self.check_given(i, value)?;
+        i += 1;
+      }
+    }
+    Ok(())
+  }
 
+  fn read_decimal(&mut self, buffer: &str) -> Result<(), SudokuError> {
     let mut i = 0;
     let mut it = buffer.chars();
     let mut buff = String::new();
+    let (mut line, mut column) = (1, 0);
+    let mut start = (line, column);
     loop {
       let mut finish = false;
       let mut increment = false;
       loop  {
         match it.next() {
           Some(ch) => {
+            column += 1;
+            if ch == '\n' {
+              line += 1;
+              column = 0;
+            }
             if ch.is_ascii_digit() {
+              if buff.is_empty() {
+                start = (line, column);
+              }
               buff.push(ch);
             } else if ch == '.' {
               increment = true;
@@ -329,16 +793,27 @@ impl<'s, T: Note> Sudoku<'s, T> {
           }
         }
       }
-      if buff.len() > 0 {
-        let value = buff.parse::<usize>().unwrap();
+      if !buff.is_empty() {
+        let (line, column) = start;
+        let value = match buff.parse::<usize>() {
+          Ok(v) => v,
+          Err(_) => return Err(SudokuError::Parse { line, column, text: buff }),
+        };
+        if value > self.rule.note.len() {
+          return Err(SudokuError::ValueOutOfRange { line, column, value, max: self.rule.note.len() });
+        }
         if value > 0 {
-          self.collapse(i, value - 1);
+          i = self.skip_ignored(i);
+          if i >= self.rule.size {
+            break;
+          }
+          self.check_given(i, value - 1)?;
           i += 1;
         }
         buff.clear();
       }
       if increment {
-        i += 1;
+        i = self.skip_ignored(i) + 1;
       }
 
       if finish || i >= self.rule.size {
@@ -349,33 +824,117 @@ impl<'s, T: Note> Sudoku<'s, T> {
     Ok(())
   }
 
+  /// Remove the given at `index` and recompute the board from the remaining
+  /// givens, starting over from an empty board so constraints prune it again.
   pub fn unfixed(&mut self, index: usize) {
     let rule = self.rule;
     self.flags[index] &= !FLAG_FIXED;
 
+    let mut result = Sudoku::new(rule);
+    result.selector = self.selector;
     for i in 0 .. rule.size {
-      self.flags[i] &= !FLAG_OK;
-      if self.flags[i] & FLAG_FIXED != FLAG_FIXED {
-        self.board[i] = rule.all;
-      }
-    }
-
-    for i in 0 .. rule.size {
+      result.flags[i] |= self.flags[i] & FLAG_IGNORED;
       if (self.flags[i] & FLAG_FIXED) == FLAG_FIXED {
         let value = get_note_index(&rule.note, self.board[i]);
         assert_ne!(value, usize::MAX);
-        self.collapse(i, value);
+        result.flags[i] |= FLAG_FIXED;
+        result.collapse(i, value);
+      }
+    }
+    result.trail.clear();
+    *self = result;
+  }
+
+  /// Generate a puzzle with a unique solution, removing up to
+  /// `remove_amount` percent of the cells. Return None when the rule has no
+  /// solution.
+  pub fn generate(rule: &'s Rule<T>, remove_amount: f32) -> Option<Sudoku<'s, T>> {
+    let mut s = Sudoku::new(rule);
+
+    {
+      let mut solutions = vec![];
+      s.solve_random(&mut solutions, 1);
+      s = solutions.pop()?;
+      s.make_fixed();
+    }
+
+    let mut list: Vec<usize> = (0 .. rule.size).filter(|&i| s.flags[i] & FLAG_IGNORED != FLAG_IGNORED).collect();
+    list.shuffle(&mut thread_rng());
+
+    let end = (list.len() as f32 * remove_amount / 100.0).floor().abs() as usize;
+    for _ in 0 .. end {
+      let index = match list.pop() {
+        Some(v) => v,
+        None => break,
+      };
+      let mut copy = s.clone();
+      copy.unfixed(index);
+      let solution_count = copy.count_solution(2);
+      if solution_count == 1 {
+        s.unfixed(index);
       }
     }
+    Some(s)
   }
 
 }
 
+impl<T: Note> Sudoku<'_, T> {
+  fn symbol(&self, i: usize) -> String {
+    let rule = self.rule;
+    if (self.flags[i] & FLAG_IGNORED) == FLAG_IGNORED {
+      " ".to_string()
+    } else if self.flags[i] != FLAG_NONE {
+      match get_note_index::<T>(&rule.note, self.board[i]) {
+        usize::MAX => ".".to_string(),
+        n => rule.alphabet.symbol(n),
+      }
+    } else {
+      ".".to_string()
+    }
+  }
+
+  // Print with `|` and `-` between cells of different regions.
+  fn fmt_regions(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    let rule = self.rule;
+    let regions = &rule.regions;
+    let space = rule.alphabet.width(rule.note.len());
+    for y in 0 .. rule.height {
+      for x in 0 .. rule.width {
+        let i = y * rule.width + x;
+        if x > 0 {
+          write!(f, "{}", if regions[i - 1] != regions[i] { '|' } else { ' ' })?
+        }
+        write!(f, "{: >space$}", self.symbol(i))?;
+      }
+      if y < rule.height - 1 {
+        writeln!(f)?;
+        let mut border = String::new();
+        for x in 0 .. rule.width {
+          let i = y * rule.width + x;
+          if x > 0 {
+            border.push(' ');
+          }
+          let ch = if regions[i] != regions[i + rule.width] { '-' } else { ' ' };
+          border.extend(std::iter::repeat_n(ch, space));
+        }
+        if border.contains('-') {
+          writeln!(f, "{}", border.trim_end())?;
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
 impl<T: Note> Display for Sudoku<'_, T> {
   fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
     let rule = self.rule;
+    if !rule.regions.is_empty() {
+      return self.fmt_regions(f);
+    }
     let mut i = 0;
-    let space: usize = (rule.note.len().ilog10() + 1).try_into().unwrap();
+    let space = rule.alphabet.width(rule.note.len());
     for y in 0 .. rule.height {
       for x in 0 .. rule.width {
         if x > 0 {
@@ -384,16 +943,7 @@ impl<T: Note> Display for Sudoku<'_, T> {
         if x % rule.x_split == 0 && x > 0 {
           write!(f, " ")?
         }
-        write!(f, "{: >space$}", if (self.flags[i] & FLAG_IGNORED) == FLAG_IGNORED {
-          " ".to_string()
-        } else if self.flags[i] != FLAG_NONE {
-          match get_note_index::<T>(&rule.note, self.board[i]) {
-            usize::MAX => ".".to_string(),
-            n => (n + 1).to_string()
-          }
-        } else {
-          ".".to_string()
-        })?;
+        write!(f, "{: >space$}", self.symbol(i))?;
         i += 1;
       }
       if y < rule.height - 1 {
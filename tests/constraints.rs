@@ -0,0 +1,159 @@
+use sudoku_rs::{
+  constraint::g_adjacent,
+  Arrow,
+  ConstraintListGenerator,
+  KillerCage,
+  Kropki,
+  Line,
+  LineKind,
+  NoDuplicate,
+  Rule,
+  Sandwich,
+  SudokuError,
+  Sudoku,
+  Thermometer,
+  Xv,
+};
+
+// Empty 4x4 board with rows, columns and 2x2 boxes.
+fn small_rule() -> Rule<u16> {
+  let mut rule = Rule::<u16>::new(4, 4, 4, 1).unwrap();
+  rule.set_grid(2, 2);
+  let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  nodup.add_standard_group(2, 2, 2, 2, 0, 0).unwrap();
+  nodup.apply(&mut rule).unwrap();
+  rule
+}
+
+// Candidates of a cell, 1-based.
+fn candidates(s: &Sudoku<u16>, index: usize) -> Vec<usize> {
+  (0 .. s.rule.note.len()).filter(|&v| s.board[index] & s.rule.note[v] != 0).map(|v| v + 1).collect()
+}
+
+#[test]
+fn thermometer_prunes_along_the_line() {
+  let mut rule = small_rule();
+  Thermometer::new(vec![0, 1, 2]).apply(&mut rule).unwrap();
+  let mut s = Sudoku::new(&rule);
+  assert_eq!(candidates(&s, 0), vec![1, 2]);
+  assert_eq!(candidates(&s, 1), vec![2, 3]);
+  assert_eq!(candidates(&s, 2), vec![3, 4]);
+
+  s.collapse(1, 2);
+  assert_eq!(candidates(&s, 0), vec![1, 2]);
+  assert_eq!(candidates(&s, 2), vec![4]);
+}
+
+#[test]
+fn killer_cage_keeps_values_reaching_the_sum() {
+  let mut rule = small_rule();
+  KillerCage::new(vec![0, 1], 3).apply(&mut rule).unwrap();
+  let mut s = Sudoku::new(&rule);
+  assert_eq!(candidates(&s, 0), vec![1, 2]);
+  assert_eq!(candidates(&s, 1), vec![1, 2]);
+  assert_eq!(candidates(&s, 4), vec![1, 2, 3, 4]);
+
+  s.collapse(0, 0);
+  assert_eq!(candidates(&s, 1), vec![2]);
+}
+
+#[test]
+fn arrow_bounds_circle_and_shaft() {
+  let mut rule = small_rule();
+  Arrow::new(0, vec![1, 2]).apply(&mut rule).unwrap();
+  let mut s = Sudoku::new(&rule);
+  assert_eq!(candidates(&s, 0), vec![2, 3, 4]);
+  assert_eq!(candidates(&s, 1), vec![1, 2, 3]);
+  assert_eq!(candidates(&s, 2), vec![1, 2, 3]);
+
+  s.collapse(1, 0);
+  assert_eq!(candidates(&s, 0), vec![3, 4]);
+  assert_eq!(candidates(&s, 2), vec![2, 3]);
+}
+
+#[test]
+fn kropki_dots_restrict_their_pair() {
+  let mut rule = small_rule();
+  Kropki::white().apply_pair(&mut rule, 0, 1).unwrap();
+  Kropki::black().apply_pair(&mut rule, 4, 8).unwrap();
+  let mut s = Sudoku::new(&rule);
+  assert_eq!(candidates(&s, 4), vec![1, 2, 4]);
+  assert_eq!(candidates(&s, 8), vec![1, 2, 4]);
+
+  s.collapse(0, 0);
+  assert_eq!(candidates(&s, 1), vec![2]);
+  s.collapse(8, 3);
+  assert_eq!(candidates(&s, 4), vec![2]);
+}
+
+#[test]
+fn xv_marks_restrict_their_pair() {
+  let mut rule = small_rule();
+  Xv::v().apply_pair(&mut rule, 0, 1).unwrap();
+  let mut s = Sudoku::new(&rule);
+  assert_eq!(candidates(&s, 0), vec![1, 2, 3, 4]);
+
+  s.collapse(0, 0);
+  assert_eq!(candidates(&s, 1), vec![4]);
+}
+
+#[test]
+fn xv_negative_skips_marked_pairs() {
+  let mut rule = small_rule();
+  let mut negative = ConstraintListGenerator::new(Xv::negative(vec![(0, 1)]), &rule);
+  negative.add_every(g_adjacent);
+  negative.apply(&mut rule).unwrap();
+  let mut s = Sudoku::new(&rule);
+
+  s.collapse(0, 0);
+  assert_eq!(candidates(&s, 1), vec![2, 3, 4]);
+  assert_eq!(candidates(&s, 4), vec![2, 3]);
+}
+
+#[test]
+fn sandwich_places_the_crust() {
+  let mut rule = small_rule();
+  Sandwich::new(vec![0, 1, 2, 3], 5).apply(&mut rule).unwrap();
+  let mut s = Sudoku::new(&rule);
+  assert_eq!(candidates(&s, 0), vec![1, 4]);
+  assert_eq!(candidates(&s, 1), vec![2, 3]);
+  assert_eq!(candidates(&s, 2), vec![2, 3]);
+  assert_eq!(candidates(&s, 3), vec![1, 4]);
+
+  s.collapse(0, 3);
+  assert_eq!(candidates(&s, 3), vec![1]);
+}
+
+#[test]
+fn lines_restrict_their_cells() {
+  let mut rule = small_rule();
+  Line::new(LineKind::Whispers(3), vec![14, 15]).apply(&mut rule).unwrap();
+  Line::renban(vec![4, 5, 6]).apply(&mut rule).unwrap();
+  Line::from_steps(LineKind::Palindrome, 4, 4, 3, &[(-1, 1), (-1, 1), (-1, 1)]).unwrap().apply(&mut rule).unwrap();
+  let mut s = Sudoku::new(&rule);
+  assert_eq!(candidates(&s, 14), vec![1, 4]);
+  assert_eq!(candidates(&s, 15), vec![1, 4]);
+
+  s.collapse(4, 0);
+  assert_eq!(candidates(&s, 5), vec![2, 3]);
+  assert_eq!(candidates(&s, 6), vec![2, 3]);
+  s.collapse(3, 1);
+  assert_eq!(candidates(&s, 12), vec![2]);
+}
+
+#[test]
+fn lines_follow_adjacent_cells() {
+  let mut rule = small_rule();
+  assert!(Line::from_steps(LineKind::Renban, 4, 4, 0, &[(1, 0), (0, -1)]).is_none());
+  assert!(matches!(Line::renban(vec![0, 5, 2]).apply(&mut rule), Ok(())));
+  assert!(matches!(Line::palindrome(vec![0, 1, 3]).apply(&mut rule), Err(SudokuError::InvalidLine(_))));
+}
+
+#[test]
+fn diagonal_must_fit_the_board() {
+  let rule = small_rule();
+  let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  nodup.add_diagonal_group(4, 0, 0).unwrap();
+  assert!(matches!(nodup.add_diagonal_group(4, 1, 0), Err(SudokuError::InvalidIndex { .. })));
+  assert!(matches!(nodup.add_diagonal_group(3, 0, 2), Err(SudokuError::InvalidIndex { .. })));
+}
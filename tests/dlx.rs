@@ -0,0 +1,34 @@
+use sudoku_rs::{ConstraintListGenerator, Dlx, NoDuplicate, Rule, Sudoku};
+
+fn standard_rule() -> Rule<u16> {
+  let mut rule = Rule::<u16>::new(9, 9, 9, 1).unwrap();
+  rule.set_grid(3, 3);
+  let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  nodup.add_standard_group(3, 3, 3, 3, 0, 0).unwrap();
+  nodup.apply(&mut rule).unwrap();
+  rule
+}
+
+fn sorted_lines(solutions: &[Sudoku<u16>]) -> Vec<String> {
+  let mut lines: Vec<String> = solutions.iter().map(|s| s.to_line()).collect();
+  lines.sort();
+  lines
+}
+
+#[test]
+fn dlx_matches_backtracking_on_samples() {
+  let rule = standard_rule();
+  for n in 1 ..= 5 {
+    let filename = format!("{}/sample/{}.sudoku", env!("CARGO_MANIFEST_DIR"), n);
+    let mut s = Sudoku::new(&rule);
+    s.read_from_file(&filename).unwrap();
+
+    let mut expected = vec![];
+    s.clone().solve(&mut expected, 2);
+    let mut found = vec![];
+    Dlx::new(&s).unwrap().solve(&mut found, 2);
+
+    assert!(!expected.is_empty(), "sample {} has no solution", n);
+    assert_eq!(sorted_lines(&found), sorted_lines(&expected), "sample {}", n);
+  }
+}
@@ -0,0 +1,65 @@
+use sudoku_rs::{Alphabet, ConstraintListGenerator, NoDuplicate, Rule, Sudoku, SudokuError};
+
+// Standard rule with boxes of `width` x `height` cells.
+fn standard_rule(width: usize, height: usize) -> Rule<u16> {
+  let size = width * height;
+  let mut rule = Rule::<u16>::new(size, size, size as u32, 1).unwrap();
+  rule.set_grid(width, height);
+  let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  nodup.add_standard_group(height, width, width, height, 0, 0).unwrap();
+  nodup.apply(&mut rule).unwrap();
+  rule
+}
+
+// Print a puzzle and read it back under the same rule.
+fn assert_round_trip(rule: &Rule<u16>) {
+  let puzzle = Sudoku::generate(rule, 50.0).unwrap();
+  let mut copy = Sudoku::new(rule);
+  copy.read_str(&puzzle.to_string()).unwrap();
+  assert_eq!(copy.to_line(), puzzle.to_line());
+}
+
+#[test]
+fn hex_alphabet_round_trip() {
+  let mut rule = standard_rule(4, 4);
+  rule.set_alphabet(Alphabet::named("hex", 16).unwrap()).unwrap();
+  assert_round_trip(&rule);
+}
+
+#[test]
+fn letters_alphabet_round_trip() {
+  let mut rule = standard_rule(3, 2);
+  rule.set_alphabet(Alphabet::named("letters", 6).unwrap()).unwrap();
+  assert_round_trip(&rule);
+}
+
+#[test]
+fn alphabet_errors() {
+  assert!(matches!(Alphabet::named("digits", 16), Err(SudokuError::InvalidAlphabet(_))));
+  assert!(matches!(Alphabet::symbols("ABCA"), Err(SudokuError::InvalidAlphabet(_))));
+  let mut rule = standard_rule(2, 2);
+  assert!(matches!(rule.set_alphabet(Alphabet::named("hex", 16).unwrap()), Err(SudokuError::InvalidAlphabet(_))));
+}
+
+#[test]
+fn line_round_trip() {
+  let rule = standard_rule(3, 3);
+  let filename = format!("{}/sample/1.sudoku", env!("CARGO_MANIFEST_DIR"));
+  let mut s = Sudoku::new(&rule);
+  s.read_from_file(&filename).unwrap();
+  let line = s.to_line();
+  assert_eq!(line.chars().count(), 81);
+
+  let mut copy = Sudoku::new(&rule);
+  copy.read_line(&line).unwrap();
+  assert_eq!(copy.to_line(), line);
+  assert_eq!(copy.board, s.board);
+}
+
+#[test]
+fn line_with_missing_cells() {
+  let rule = standard_rule(3, 3);
+  let mut s = Sudoku::new(&rule);
+  assert!(matches!(s.read_line(&".".repeat(80)), Err(SudokuError::CellCount { expected: 81, found: 80 })));
+  assert!(matches!(s.read_line(&format!("x{}", ".".repeat(80))), Err(SudokuError::Parse { column: 1, .. })));
+}
@@ -0,0 +1,38 @@
+use std::fs;
+
+use sudoku_rs::{fpuzzles::read_fpuzzles, Sudoku, SudokuError};
+
+#[test]
+fn imports_the_sample() {
+  let text = fs::read_to_string(format!("{}/sample/x-killer.json", env!("CARGO_MANIFEST_DIR"))).unwrap();
+  let (spec, grid) = read_fpuzzles(&text).unwrap();
+  assert_eq!((spec.width, spec.height), (3, 3));
+  assert!(spec.diagonals);
+  let directives: Vec<&str> = spec.directives.iter().map(|(_, text)| text.as_str()).collect();
+  assert_eq!(directives, vec!["killer 19 r1c1 r1c2 r2c1", "killer 22 r5c4 r5c5 r5c6", "thermo r5c1 r5c2 r5c3"]);
+
+  let rule = spec.build(1u16).unwrap();
+  let mut s = Sudoku::new(&rule);
+  s.read_str(&grid).unwrap();
+  assert_eq!(s.count_solution(2), 1);
+}
+
+fn puzzle(features: &str) -> String {
+  let row = format!("[{}]", ["{}"; 4].join(", "));
+  format!("{{\"size\": 4, \"grid\": [{}], {}}}", vec![row; 4].join(", "), features)
+}
+
+#[test]
+fn rejects_unsupported_features() {
+  let text = puzzle("\"fogofwar\": [\"R1C1\"]");
+  assert!(matches!(read_fpuzzles(&text), Err(SudokuError::Unsupported(_))));
+}
+
+#[test]
+fn names_the_entry_of_an_error() {
+  let text = puzzle("\"killercage\": [{\"cells\": [\"R1C1\"], \"value\": \"3\"}, {\"cells\": [], \"value\": \"5\"}]");
+  match read_fpuzzles(&text) {
+    Err(SudokuError::InvalidImport(what)) => assert!(what.starts_with("killercage[1]:"), "{}", what),
+    other => panic!("unexpected {:?}", other.map(|(_, grid)| grid)),
+  }
+}
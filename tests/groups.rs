@@ -0,0 +1,67 @@
+use std::fs;
+
+use sudoku_rs::{
+  constraint::{GGRegion, GGWindow, GroupGenerator},
+  ConstraintListGenerator,
+  NoDuplicate,
+  Rule,
+  RuleSpec,
+  Sudoku,
+  SudokuError,
+};
+
+fn read_sample(name: &str) -> String {
+  fs::read_to_string(format!("{}/sample/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap()
+}
+
+fn empty_rule(size: usize) -> Rule<u16> {
+  Rule::<u16>::new(size, size, size as u32, 1).unwrap()
+}
+
+#[test]
+fn regions_from_a_map() {
+  let rule = empty_rule(9);
+  let nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  let regions = GGRegion::parse(&nodup, &read_sample("jigsaw.regions")).unwrap();
+  assert_eq!(regions.get_size(), (9, 9));
+  assert_eq!(&regions.map()[.. 9], &[0, 0, 0, 0, 1, 1, 2, 2, 2]);
+}
+
+#[test]
+fn regions_of_unequal_size() {
+  let rule = empty_rule(4);
+  let nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  let result = GGRegion::parse(&nodup, "AABB\nAABB\nACCD\nCCDD\n");
+  assert!(matches!(result, Err(SudokuError::InvalidRegions(_))));
+}
+
+#[test]
+fn region_borders_round_trip() {
+  let spec = RuleSpec {
+    regions: Some(read_sample("jigsaw.regions")),
+    ..RuleSpec::default()
+  };
+  let rule = spec.build(1u16).unwrap();
+  let mut s = Sudoku::new(&rule);
+  s.read_str(&read_sample("jigsaw.sudoku")).unwrap();
+
+  let text = s.to_string();
+  assert!(text.contains('|') && text.contains('-'));
+  let mut copy = Sudoku::new(&rule);
+  copy.read_str(&text).unwrap();
+  assert_eq!(copy.to_line(), s.to_line());
+}
+
+#[test]
+fn windows_of_a_9x9_board() {
+  let rule = empty_rule(9);
+  let nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  let windows = GGWindow::new(&nodup, 3, 3, 3, 3, 0, 0);
+  assert!(windows.fits());
+  assert_eq!(windows.get_size(), (4, 9));
+  let cells = |group| (0 .. 9).map(|member| windows.get_member(group, member)).collect::<Vec<_>>();
+  assert_eq!(cells(0), vec![10, 11, 12, 19, 20, 21, 28, 29, 30]);
+  assert_eq!(cells(1), vec![14, 15, 16, 23, 24, 25, 32, 33, 34]);
+  assert_eq!(cells(2), vec![46, 47, 48, 55, 56, 57, 64, 65, 66]);
+  assert_eq!(cells(3), vec![50, 51, 52, 59, 60, 61, 68, 69, 70]);
+}
@@ -0,0 +1,54 @@
+use sudoku_rs::{
+  directive::apply_directive,
+  sudoku::FLAG_IGNORED,
+  Layout,
+  RuleSpec,
+  Sudoku,
+  SudokuError,
+};
+
+#[test]
+fn grid_offsets() {
+  let twodoku = Layout::twodoku(3, 3);
+  assert_eq!(twodoku.grids(), &[(0, 0), (6, 6)]);
+  assert_eq!((twodoku.width, twodoku.height), (15, 15));
+
+  let butterfly = Layout::butterfly(3, 3);
+  assert_eq!(butterfly.grids(), &[(0, 0), (3, 0), (0, 3), (3, 3)]);
+  assert_eq!((butterfly.width, butterfly.height), (12, 12));
+
+  let samurai = Layout::samurai(3, 3);
+  assert_eq!(samurai.grids(), &[(0, 0), (12, 0), (6, 6), (0, 12), (12, 12)]);
+  assert_eq!((samurai.width, samurai.height), (21, 21));
+  assert!(!samurai.contains(9, 0));
+  assert!(samurai.contains(9, 6));
+}
+
+#[test]
+fn gaps_are_skipped_when_reading_and_printing() {
+  let spec = RuleSpec {
+    layout: "twodoku".to_string(),
+    ..RuleSpec::default()
+  };
+  let rule = spec.build(1u16).unwrap();
+  let puzzle = Sudoku::generate(&rule, 50.0).unwrap();
+  let ignored = puzzle.flags.iter().filter(|&&flag| flag & FLAG_IGNORED == FLAG_IGNORED).count();
+  assert_eq!(ignored, 15 * 15 - (81 * 2 - 9));
+  assert_eq!(puzzle.to_line().len(), 81 * 2 - 9);
+
+  let mut copy = Sudoku::new(&rule);
+  copy.read_str(&puzzle.to_string()).unwrap();
+  assert_eq!(copy.to_line(), puzzle.to_line());
+}
+
+#[test]
+fn directives_stay_inside_the_grids() {
+  let spec = RuleSpec {
+    layout: "samurai".to_string(),
+    ..RuleSpec::default()
+  };
+  let mut rule = spec.build(1u16).unwrap();
+  apply_directive(&mut rule, 1, "thermo r1c8 r1c9").unwrap();
+  assert!(matches!(apply_directive(&mut rule, 1, "thermo r1c9 r1c10"), Err(SudokuError::Parse { column: 13, .. })));
+  assert!(matches!(apply_directive(&mut rule, 1, "sandwich r1 5"), Err(SudokuError::Parse { column: 10, .. })));
+}
@@ -0,0 +1,70 @@
+use sudoku_rs::{ConstraintListGenerator, NoDuplicate, Rule, Sudoku};
+
+// Note holding the 1-based `values`.
+fn note(rule: &Rule<u16>, values: &[usize]) -> u16 {
+  values.iter().fold(rule.zero, |note, &v| note | rule.note[v - 1])
+}
+
+// One row of 4 cells holding 1 to 4.
+fn row_rule() -> Rule<u16> {
+  let mut rule = Rule::<u16>::new(4, 1, 4, 1).unwrap();
+  rule.add_group(vec![0, 1, 2, 3]).unwrap();
+  rule
+}
+
+#[test]
+fn hidden_single() {
+  let rule = row_rule();
+  let mut s = Sudoku::new(&rule);
+  for i in 1 .. 4 {
+    s.set_note(i, note(&rule, &[2, 3, 4]));
+  }
+  s.collapse_avail().unwrap();
+  assert_eq!(s.board[0], note(&rule, &[1]));
+  assert_eq!(s.board[1], note(&rule, &[2, 3, 4]));
+}
+
+#[test]
+fn naked_pair() {
+  let rule = row_rule();
+  let mut s = Sudoku::new(&rule);
+  s.set_note(0, note(&rule, &[1, 2]));
+  s.set_note(1, note(&rule, &[1, 2]));
+  s.collapse_avail().unwrap();
+  assert_eq!(s.board[2], note(&rule, &[3, 4]));
+  assert_eq!(s.board[3], note(&rule, &[3, 4]));
+}
+
+#[test]
+fn naked_triple() {
+  let rule = row_rule();
+  let mut s = Sudoku::new(&rule);
+  s.set_note(0, note(&rule, &[1, 2]));
+  s.set_note(1, note(&rule, &[2, 3]));
+  s.set_note(2, note(&rule, &[1, 3]));
+  s.collapse_avail().unwrap();
+  assert_eq!(s.board[3], note(&rule, &[4]));
+}
+
+#[test]
+fn pointing_into_a_variant_group() {
+  // Two rows of 4 cells, with an extra group over the first two cells of
+  // each row.
+  let mut rule = Rule::<u16>::new(4, 2, 4, 1).unwrap();
+  let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  nodup.add_horizontal_group(4, 2, 0, 0).unwrap();
+  nodup.add_group(&[0, 1, 4, 5]).unwrap();
+  nodup.apply(&mut rule).unwrap();
+  assert_eq!(rule.groups().len(), 3);
+
+  let mut s = Sudoku::new(&rule);
+  s.set_note(2, note(&rule, &[1, 2, 3]));
+  s.set_note(3, note(&rule, &[1, 2, 3]));
+  s.collapse_avail().unwrap();
+  // 4 of the first row is in the extra group, so the second row can't hold
+  // it there.
+  assert_eq!(s.board[4], note(&rule, &[1, 2, 3]));
+  assert_eq!(s.board[5], note(&rule, &[1, 2, 3]));
+  assert_eq!(s.board[6], rule.all);
+  assert_eq!(s.board[0], rule.all);
+}
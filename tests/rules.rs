@@ -0,0 +1,91 @@
+use std::fs;
+
+use sudoku_rs::{RuleSpec, Sudoku, SudokuError};
+
+fn read_sample(name: &str) -> String {
+  fs::read_to_string(format!("{}/sample/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap()
+}
+
+#[test]
+fn rule_file_builds_its_rule() {
+  let mut spec = RuleSpec::default();
+  spec.read_str(&read_sample("x-killer.rules")).unwrap();
+  assert_eq!((spec.width, spec.height), (3, 3));
+  assert!(spec.diagonals);
+  let directives: Vec<&str> = spec.directives.iter().map(|(_, text)| text.trim()).collect();
+  assert_eq!(directives, vec!["killer 19 r1c1 r1c2 r2c1", "killer 22 r5c4 r5c5 r5c6", "thermo r5c1 r5c2 r5c3"]);
+
+  let rule = spec.build(1u16).unwrap();
+  let mut s = Sudoku::new(&rule);
+  s.read_str(&read_sample("x-killer.sudoku")).unwrap();
+  assert_eq!(s.count_solution(2), 1);
+}
+
+#[test]
+fn negative_rule_skips_puzzle_dots() {
+  let mut grid = "1 2 . . . . . . .\n".to_string();
+  grid += &". . . . . . . . .\n".repeat(8);
+  let mut spec = RuleSpec::default();
+  spec.read_str("kropki-negative\n").unwrap();
+  let rule = spec.build(1u16).unwrap();
+  assert!(Sudoku::new(&rule).read_str(&grid).is_err());
+
+  let puzzle = format!("!white r1c1 r1c2\n{}", grid);
+  spec.read_directives(&puzzle);
+  let rule = spec.build(1u16).unwrap();
+  Sudoku::new(&rule).read_str(&puzzle).unwrap();
+}
+
+#[test]
+fn header_round_trip() {
+  let mut spec = RuleSpec::default();
+  spec.read_str("layout twodoku\nanti-king\nxv-negative\nx r1c1 r1c2\nkiller 10 r2c1 r2c2\nkiller 10 r2c1 r2c2\n").unwrap();
+  assert_eq!(spec.directives.len(), 3);
+  let header = spec.to_header();
+
+  let mut copy = RuleSpec::default();
+  copy.read_header(&format!("{}. . .\n", header)).unwrap();
+  assert_eq!(copy.to_header(), header);
+  assert_eq!(copy.directives.len(), 3);
+
+  // A header written from the rule file doesn't repeat its directives.
+  spec.read_header(&header).unwrap();
+  assert_eq!(spec.directives.len(), 3);
+}
+
+#[test]
+fn negative_rules_skip_gaps_between_grids() {
+  let spec = RuleSpec {
+    layout: "twodoku".to_string(),
+    xv_negative: true,
+    ..RuleSpec::default()
+  };
+  let rule = spec.build(1u16).unwrap();
+  assert!(Sudoku::new(&rule).count_solution(1) > 0);
+}
+
+#[test]
+fn rule_file_adds_groups() {
+  let mut spec = RuleSpec::default();
+  spec.read_str("group r1c1 r2c2 r3c3 r4c4\n").unwrap();
+  let rule = spec.build(1u16).unwrap();
+  assert!(rule.groups().contains(&vec![0, 10, 20, 30]));
+  let mut grid = "5 . . . . . . . .\n".to_string();
+  grid += &". . . . . . . . .\n".repeat(2);
+  grid += ". . . 5 . . . . .\n";
+  grid += &". . . . . . . . .\n".repeat(5);
+  assert!(matches!(Sudoku::new(&rule).read_str(&grid), Err(SudokuError::Contradiction { .. })));
+
+  let mut spec = RuleSpec::default();
+  spec.read_str("group r1c1 r1c2 r1c3 r1c4 r1c5 r1c6 r1c7 r1c8 r1c9 r2c1\n").unwrap();
+  assert!(matches!(spec.build(1u16), Err(SudokuError::Parse { line: 1, column: 52, .. })));
+}
+
+#[test]
+fn puzzle_directives_already_in_the_rules_apply_once() {
+  let mut spec = RuleSpec::default();
+  spec.read_str("thermo r1c1 r1c2 r1c3\n").unwrap();
+  spec.read_directives("!thermo r1c1  r1c2 r1c3\n!white r2c1 r2c2\n!white r2c1 r2c2\n");
+  let directives: Vec<&str> = spec.directives.iter().map(|(_, text)| text.trim()).collect();
+  assert_eq!(directives, vec!["thermo r1c1 r1c2 r1c3", "white r2c1 r2c2", "white r2c1 r2c2"]);
+}
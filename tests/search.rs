@@ -0,0 +1,42 @@
+use sudoku_rs::{ConstraintListGenerator, NoDuplicate, Rule, Sudoku, Thermometer};
+
+fn standard_rule() -> Rule<u16> {
+  let mut rule = Rule::<u16>::new(9, 9, 9, 1).unwrap();
+  rule.set_grid(3, 3);
+  let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  nodup.add_standard_group(3, 3, 3, 3, 0, 0).unwrap();
+  nodup.apply(&mut rule).unwrap();
+  rule
+}
+
+#[test]
+fn search_leaves_the_board_as_is() {
+  let rule = standard_rule();
+  for n in 1 ..= 5 {
+    let filename = format!("{}/sample/{}.sudoku", env!("CARGO_MANIFEST_DIR"), n);
+    let mut s = Sudoku::new(&rule);
+    s.read_from_file(&filename).unwrap();
+    let (board, flags) = (s.board.clone(), s.flags.clone());
+
+    assert!(s.count_solution(2) > 0);
+    assert_eq!((&s.board, &s.flags), (&board, &flags));
+    let mut solutions = vec![];
+    s.solve(&mut solutions, 2);
+    assert!(!solutions.is_empty());
+    assert_eq!((&s.board, &s.flags), (&board, &flags));
+  }
+}
+
+#[test]
+fn unfixed_keeps_the_pruning_of_constraints() {
+  let mut rule = standard_rule();
+  Thermometer::new((0 .. 9).map(|row| row * 9).collect()).apply(&mut rule).unwrap();
+  let mut s = Sudoku::new(&rule);
+  assert_eq!(s.board[9], rule.note[1]);
+  s.collapse(0, 0);
+  s.make_fixed();
+
+  s.unfixed(0);
+  assert_eq!(s.board[0], rule.note[0]);
+  assert_eq!(s.board[9], rule.note[1]);
+}
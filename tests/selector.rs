@@ -0,0 +1,49 @@
+use sudoku_rs::{CellSelector, ConstraintListGenerator, FirstUnsolved, MinRemaining, MinRemainingDegree, NoDuplicate, Rule, Sudoku};
+
+fn standard_rule(width: usize, height: usize) -> Rule<u16> {
+  let size = width * height;
+  let mut rule = Rule::<u16>::new(size, size, size as u32, 1).unwrap();
+  rule.set_grid(width, height);
+  let mut nodup = ConstraintListGenerator::new(NoDuplicate::new(), &rule);
+  nodup.add_standard_group(height, width, width, height, 0, 0).unwrap();
+  nodup.apply(&mut rule).unwrap();
+  rule
+}
+
+// Sorted solutions found with `selector`, up to `max`.
+fn solutions<'a>(s: &Sudoku<'a, u16>, selector: &'a dyn CellSelector<u16>, max: usize) -> Vec<String> {
+  let mut s = s.clone();
+  s.set_selector(selector);
+  let mut found = vec![];
+  s.solve(&mut found, max);
+  let mut lines: Vec<String> = found.iter().map(|s| s.to_line()).collect();
+  lines.sort();
+  lines
+}
+
+fn assert_same_solutions(s: &Sudoku<u16>, max: usize) -> usize {
+  let expected = solutions(s, &FirstUnsolved, max);
+  assert_eq!(solutions(s, &MinRemaining, max), expected);
+  assert_eq!(solutions(s, &MinRemainingDegree, max), expected);
+  expected.len()
+}
+
+#[test]
+fn selectors_agree_on_the_samples() {
+  let rule = standard_rule(3, 3);
+  for n in 1 ..= 5 {
+    let filename = format!("{}/sample/{}.sudoku", env!("CARGO_MANIFEST_DIR"), n);
+    let mut s = Sudoku::new(&rule);
+    s.read_from_file(&filename).unwrap();
+    // Every solution is found, so the orders of the search don't matter.
+    let count = assert_same_solutions(&s, 1000);
+    assert!(count > 0 && count < 1000);
+  }
+}
+
+#[test]
+fn selectors_agree_on_every_4x4_grid() {
+  let rule = standard_rule(2, 2);
+  let s = Sudoku::new(&rule);
+  assert_eq!(assert_same_solutions(&s, 1000), 288);
+}